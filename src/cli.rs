@@ -4,7 +4,7 @@ use cargo_metadata::Version;
 use clap::{ArgEnum, Parser, Subcommand};
 use tracing::level_filters::LevelFilter;
 
-use crate::format::{CriteriaName, PackageName, VersionReq};
+use crate::format::{CriteriaName, ImportName, PackageName, VersionReq};
 
 #[derive(Parser)]
 #[clap(version, about, long_about = None)]
@@ -42,6 +42,22 @@ pub struct Cli {
     #[clap(requires = "locked")]
     pub frozen: bool,
 
+    /// How long to wait for each imported audits.toml to be fetched, in seconds
+    ///
+    /// Imports are fetched in parallel, and all the ones that time out or
+    /// otherwise fail are reported together.
+    #[clap(long)]
+    #[clap(default_value_t = 30)]
+    pub fetch_timeout: u64,
+
+    /// If an import can't be fetched, keep using its copy in imports.lock
+    ///
+    /// A warning says which imports are stale and how old they are. Other imports
+    /// are still updated. This can also be enabled with `allow-stale-imports = true`
+    /// in config.toml.
+    #[clap(long)]
+    pub allow_stale_imports: bool,
+
     /// How verbose logging should be (log level)
     #[clap(long)]
     #[clap(default_value_t = LevelFilter::WARN)]
@@ -124,6 +140,11 @@ pub enum Commands {
     Init(InitArgs),
 
     /// Accept changes that a foreign audits.toml made to their criteria
+    ///
+    /// imports.lock records the definitions of the foreign criteria that your
+    /// `criteria-map`s rely on, and fetching imports fails if an import has
+    /// changed any of them since. This fetches the imports, shows what changed,
+    /// and records the new definitions so that future fetches succeed.
     #[clap(disable_version_flag = true)]
     AcceptCriteriaChange(AcceptCriteriaChangeArgs),
 
@@ -151,12 +172,59 @@ pub enum Commands {
     #[clap(disable_version_flag = true)]
     Suggest(SuggestArgs),
 
+    /// Explain how `$package $version` is vetted
+    ///
+    /// For each criteria, this prints the chain of full and delta audits that
+    /// vets the version, including where each audit came from (audits.toml or
+    /// an import) and who wrote it. If there is no chain, it prints the versions
+    /// that are reachable from either end, or the dependencies to blame.
+    #[clap(disable_version_flag = true)]
+    Explain(ExplainArgs),
+
+    /// Show why `$package` needs the criteria it does
+    ///
+    /// This prints every dependency path from a root, or from the dev-build of
+    /// a workspace member, down to `$package`. Each step shows the criteria
+    /// required of that package, and whether it came from a policy or from a
+    /// `dependency-criteria` entry.
+    #[clap(disable_version_flag = true)]
+    Why(WhyArgs),
+
     /// Reformat all of vet's files (in case you hand-edited them)
     ///
-    /// All commands that access the store (supply-chain) will implicitly do this.
+    /// Comments are kept, but everything else is put back into the usual layout.
+    /// Other commands that access the store (supply-chain) only rewrite the
+    /// entries they change.
     #[clap(disable_version_flag = true)]
     Fmt(FmtArgs),
 
+    /// Upgrade the store (supply-chain) to the current format version
+    ///
+    /// Stores in an older format can still be read, and any command that writes
+    /// to the store will upgrade it, but this does it explicitly.
+    #[clap(disable_version_flag = true)]
+    Migrate(MigrateArgs),
+
+    /// Import a well-known audit set from the registry
+    ///
+    /// This looks `$name` up in the registry of well-known audit sets, adds it to
+    /// the imports in config.toml, and fetches it.
+    ///
+    /// Unless `--map-builtins` or `--no-map-builtins` is passed, this asks on the
+    /// terminal whether to map their built-in criteria to ours.
+    #[clap(disable_version_flag = true)]
+    Import(ImportArgs),
+
+    /// Merge the audits.toml files listed in `$sources` into one
+    ///
+    /// `$sources` lists a URL or path for each audits.toml, one per line.
+    /// Blank lines and lines starting with `#` are ignored, and relative paths
+    /// are relative to the directory containing `$sources`. The merged file is
+    /// written to stdout, with an `aggregated-from` field on each audit
+    /// recording where it came from.
+    #[clap(disable_version_flag = true)]
+    Aggregate(AggregateArgs),
+
     /// Explicitly fetch the imports (foreign audit files)
     ///
     /// Bare `cargo vet` will implicitly do this.
@@ -189,6 +257,22 @@ pub struct InspectArgs {
     pub version: Version,
 }
 
+/// Explains how a package is vetted
+#[derive(clap::Args)]
+pub struct ExplainArgs {
+    /// The package to explain
+    pub package: PackageName,
+    /// The version to explain
+    pub version: Version,
+}
+
+/// Shows the dependency paths that lead to a package
+#[derive(clap::Args)]
+pub struct WhyArgs {
+    /// The package to show the paths to
+    pub package: PackageName,
+}
+
 /// Emits a diff of the two versions
 #[derive(clap::Args)]
 pub struct DiffArgs {
@@ -295,13 +379,50 @@ pub struct SuggestArgs {
     /// that by making us assume the dependencies all need the same criteria as the parent.
     #[clap(long)]
     pub guess_deeper: bool,
+    /// Also look for audit sets to import in the registry, or in this
+    /// registry.toml (a URL or a path). This requires the network.
+    #[clap(long, value_name = "REGISTRY")]
+    pub registry: Option<Option<String>>,
 }
 
 #[derive(clap::Args)]
 pub struct FmtArgs {}
 
 #[derive(clap::Args)]
-pub struct FetchImportsArgs {}
+pub struct MigrateArgs {}
+
+#[derive(clap::Args)]
+pub struct ImportArgs {
+    /// The name of the audit set in the registry (also used as the import's name)
+    pub name: ImportName,
+    /// Look the audit set up in this registry.toml instead (a URL or a path)
+    #[clap(long)]
+    pub registry: Option<String>,
+    /// Map their built-in criteria to ours, without asking
+    #[clap(long)]
+    pub map_builtins: bool,
+    /// Don't map their built-in criteria to ours, and don't ask
+    #[clap(long)]
+    #[clap(conflicts_with = "map-builtins")]
+    pub no_map_builtins: bool,
+}
+
+#[derive(clap::Args)]
+pub struct AggregateArgs {
+    /// The file listing the audits.toml files to merge
+    pub sources: PathBuf,
+}
+
+#[derive(clap::Args)]
+pub struct FetchImportsArgs {
+    /// Pin each import to the sha256 of what's fetched now
+    ///
+    /// Imports with a `sha256` in config.toml are refused if their contents
+    /// don't match it. Use this to pin them in the first place, or to accept
+    /// new contents once you've reviewed them.
+    #[clap(long)]
+    pub pin: bool,
+}
 
 #[derive(clap::Args)]
 pub struct RegenerateUnauditedArgs {}
@@ -413,6 +534,8 @@ impl Cli {
             features: Features::default(),
             locked: false,
             frozen: false,
+            fetch_timeout: 30,
+            allow_stale_imports: false,
             verbose: LevelFilter::OFF,
             output_file: None,
             output_format: OutputFormat::Human,
@@ -565,11 +688,11 @@ impl FromStr for GraphFilter {
             })?;
             Ok((rest, val))
         }
-        fn ws<'a, F: 'a, O, E: ParseError<&'a str>>(
+        fn ws<'a, F, O, E: ParseError<&'a str>>(
             inner: F,
         ) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
         where
-            F: Fn(&'a str) -> IResult<&'a str, O, E>,
+            F: Fn(&'a str) -> IResult<&'a str, O, E> + 'a,
         {
             delimited(multispace0, inner, multispace0)
         }
@@ -0,0 +1,363 @@
+//! Editing hand-written TOML files without trampling on them
+//!
+//! People put comments, blank lines, and carefully formatted `notes` in the
+//! store's files, none of which survives a round-trip through serde. So when
+//! we write a file back out, we instead take what serde would have written
+//! and apply it to the existing document, only touching what changed.
+
+use toml_edit::{ArrayOfTables, Decor, DocumentMut, Item, Table, TableLike, Value};
+
+use crate::VetError;
+
+/// Rewrite `original` to have the same contents as `updated`.
+///
+/// Anything whose value didn't change is kept byte-for-byte, including
+/// comments and formatting. Entries which were added are placed after their
+/// neighbours from `updated`, entries which were removed are deleted, and
+/// entries which changed are edited in place where possible.
+pub fn update_in_place(original: &str, updated: &str) -> Result<String, VetError> {
+    let mut doc: DocumentMut = original.parse()?;
+    let updated: DocumentMut = updated.parse()?;
+    merge_table(doc.as_table_mut(), updated.as_table(), false);
+    Ok(doc.to_string())
+}
+
+/// Lay out the contents of `original` the way `updated` is, but keep its comments.
+///
+/// This is for reformatting: the structure and order of `updated` wins, but
+/// comments are carried over from `original` to the matching entries, and
+/// values which didn't change keep their original formatting (so that e.g.
+/// multi-line `notes` stay multi-line).
+pub fn reformat(original: &str, updated: &str) -> Result<String, VetError> {
+    let original: DocumentMut = original.parse()?;
+    let mut doc: DocumentMut = updated.parse()?;
+    carry_over_table(doc.as_table_mut(), original.as_table());
+    doc.set_trailing(original.trailing().clone());
+    Ok(doc.to_string())
+}
+
+fn merge_item(old: &mut Item, new: &Item) {
+    if same_item(old, new) {
+        return;
+    }
+    match (&mut *old, new) {
+        (Item::Table(old), Item::Table(new)) => merge_table(old, new, false),
+        (Item::Table(old), Item::Value(Value::InlineTable(new))) => merge_table(old, new, false),
+        (Item::ArrayOfTables(old), Item::ArrayOfTables(new)) => merge_array_of_tables(old, new),
+        // We're somewhere that needs a plain value (e.g. inside an inline
+        // table), so make the new item fit in.
+        (Item::Value(old), new) => {
+            if let Ok(new) = new.clone().into_value() {
+                merge_value(old, &new);
+            }
+        }
+        (old, new) => *old = new_item(new),
+    }
+}
+
+fn merge_value(old: &mut Value, new: &Value) {
+    if same_value(old, new) {
+        return;
+    }
+    match (&mut *old, new) {
+        (Value::InlineTable(old), Value::InlineTable(new)) => merge_table(old, new, true),
+        (Value::Array(old), Value::Array(new)) => {
+            let mut removed = Vec::new();
+            for pair in pair_up(
+                &old.iter().collect::<Vec<_>>(),
+                &new.iter().collect::<Vec<_>>(),
+                same_value,
+            ) {
+                match pair {
+                    Pair::Both(old_idx, new_idx) => {
+                        merge_value(old.get_mut(old_idx).unwrap(), new.get(new_idx).unwrap())
+                    }
+                    Pair::Old(old_idx) => removed.push(old_idx),
+                    Pair::New(new_idx) => old.push_formatted(new.get(new_idx).unwrap().clone()),
+                }
+            }
+            for old_idx in removed.into_iter().rev() {
+                old.remove(old_idx);
+            }
+        }
+        (old, new) => {
+            // Keep any comments attached to the old value
+            let decor = old.decor().clone();
+            *old = new.clone();
+            *old.decor_mut() = decor;
+        }
+    }
+}
+
+/// Merge `new` into `old`. If `inline` is set, `old` can only hold values.
+fn merge_table(old: &mut dyn TableLike, new: &dyn TableLike, inline: bool) {
+    let removed = old
+        .iter()
+        .filter(|(key, _)| !new.contains_key(key))
+        .map(|(key, _)| key.to_owned())
+        .collect::<Vec<_>>();
+    for key in removed {
+        old.remove(&key);
+    }
+
+    for (key, old_item) in old.iter_mut() {
+        if let Some(new_item) = new.get(&key) {
+            merge_item(old_item, new_item);
+        }
+    }
+    if new.len() == old.len() {
+        return;
+    }
+
+    // Place each added entry right after the one before it in `new` (so
+    // sorted tables stay sorted), leaving the existing entries where they are.
+    let mut order = old
+        .iter()
+        .map(|(key, _)| key.to_owned())
+        .collect::<Vec<_>>();
+    let mut prev = None;
+    for (key, _) in new.iter() {
+        let idx = match order.iter().position(|k| k == key) {
+            Some(idx) => idx,
+            None => {
+                let idx = prev.map_or(0, |prev| prev + 1);
+                order.insert(idx, key.to_owned());
+                idx
+            }
+        };
+        prev = Some(idx);
+    }
+
+    let mut entries = Vec::new();
+    for key in order {
+        match old.key(&key).cloned() {
+            Some(formatted_key) => {
+                let item = old.remove(&key).unwrap();
+                entries.push((formatted_key, item));
+            }
+            None => {
+                let formatted_key = new.key(&key).unwrap().clone();
+                let mut item = new_item(new.get(&key).unwrap());
+                if inline {
+                    item = item.into_value().map(Item::Value).unwrap_or(Item::None);
+                }
+                entries.push((formatted_key, item));
+            }
+        }
+    }
+    old.clear();
+    for (key, item) in entries {
+        old.entry_format(&key).or_insert(item);
+    }
+}
+
+fn merge_array_of_tables(old: &mut ArrayOfTables, new: &ArrayOfTables) {
+    let mut removed = Vec::new();
+    for pair in pair_up(
+        &old.iter().collect::<Vec<_>>(),
+        &new.iter().collect::<Vec<_>>(),
+        same_table,
+    ) {
+        match pair {
+            Pair::Both(old_idx, new_idx) => merge_table(
+                old.get_mut(old_idx).unwrap(),
+                new.get(new_idx).unwrap(),
+                false,
+            ),
+            Pair::Old(old_idx) => removed.push(old_idx),
+            Pair::New(new_idx) => old.push(new_table(new.get(new_idx).unwrap())),
+        }
+    }
+    for old_idx in removed.into_iter().rev() {
+        old.remove(old_idx);
+    }
+}
+
+/// Copy an item from another document.
+fn new_item(item: &Item) -> Item {
+    match item {
+        Item::Table(table) => Item::Table(new_table(table)),
+        Item::ArrayOfTables(tables) => {
+            let mut new = ArrayOfTables::new();
+            for table in tables.iter() {
+                new.push(new_table(table));
+            }
+            Item::ArrayOfTables(new)
+        }
+        item => item.clone(),
+    }
+}
+
+/// Copy a table from another document.
+///
+/// The copy doesn't remember where it was in the other document, so it'll be
+/// written out after whatever precedes it in this one.
+fn new_table(table: &Table) -> Table {
+    let mut new = Table::new();
+    new.set_implicit(table.is_implicit());
+    new.set_dotted(table.is_dotted());
+    *new.decor_mut() = Decor::new("\n", "");
+    for (key, item) in table.iter() {
+        new.insert_formatted(table.key(key).unwrap(), new_item(item));
+    }
+    new
+}
+
+fn carry_over_item(new: &mut Item, old: &Item) {
+    match (new, old) {
+        (Item::Table(new), Item::Table(old)) => {
+            *new.decor_mut() = old.decor().clone();
+            carry_over_table(new, old);
+        }
+        (Item::Table(new), Item::Value(Value::InlineTable(old))) => carry_over_table(new, old),
+        (Item::ArrayOfTables(new), Item::ArrayOfTables(old)) => {
+            for pair in pair_up(
+                &old.iter().collect::<Vec<_>>(),
+                &new.iter().collect::<Vec<_>>(),
+                same_table,
+            ) {
+                if let Pair::Both(old_idx, new_idx) = pair {
+                    let old = old.get(old_idx).unwrap();
+                    let new = new.get_mut(new_idx).unwrap();
+                    *new.decor_mut() = old.decor().clone();
+                    carry_over_table(new, old);
+                }
+            }
+        }
+        (Item::Value(new), Item::Value(old)) => carry_over_value(new, old),
+        _ => {}
+    }
+}
+
+fn carry_over_value(new: &mut Value, old: &Value) {
+    if same_value(new, old) {
+        *new = old.clone();
+        return;
+    }
+    match (new, old) {
+        (Value::InlineTable(new), Value::InlineTable(old)) => carry_over_table(new, old),
+        (Value::Array(new), Value::Array(old)) => {
+            for pair in pair_up(
+                &old.iter().collect::<Vec<_>>(),
+                &new.iter().collect::<Vec<_>>(),
+                same_value,
+            ) {
+                if let Pair::Both(old_idx, new_idx) = pair {
+                    carry_over_value(new.get_mut(new_idx).unwrap(), old.get(old_idx).unwrap());
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn carry_over_table(new: &mut dyn TableLike, old: &dyn TableLike) {
+    for (mut key, item) in new.iter_mut() {
+        if let Some((old_key, old_item)) = old.get_key_value(key.get()) {
+            *key.leaf_decor_mut() = old_key.leaf_decor().clone();
+            carry_over_item(item, old_item);
+        }
+    }
+}
+
+/// How the elements of an old and new array correspond.
+enum Pair {
+    Both(usize, usize),
+    Old(usize),
+    New(usize),
+}
+
+/// Work out which elements of `old` became which elements of `new`.
+///
+/// Identical elements are paired up first. Whatever's left over is then
+/// paired up in order, on the assumption that those are edits.
+fn pair_up<T: ?Sized>(old: &[&T], new: &[&T], same: impl Fn(&T, &T) -> bool) -> Vec<Pair> {
+    let mut old_used = vec![false; old.len()];
+    let mut new_matches = vec![None; new.len()];
+    for (new_idx, new) in new.iter().enumerate() {
+        let found = (0..old.len()).find(|&old_idx| !old_used[old_idx] && same(old[old_idx], new));
+        if let Some(old_idx) = found {
+            old_used[old_idx] = true;
+            new_matches[new_idx] = Some(old_idx);
+        }
+    }
+
+    let mut leftover = (0..old.len()).filter(|&old_idx| !old_used[old_idx]);
+    let mut pairs = Vec::new();
+    for (new_idx, old_idx) in new_matches.into_iter().enumerate() {
+        match old_idx.or_else(|| leftover.next()) {
+            Some(old_idx) => pairs.push(Pair::Both(old_idx, new_idx)),
+            None => pairs.push(Pair::New(new_idx)),
+        }
+    }
+    pairs.extend(leftover.map(Pair::Old));
+    pairs
+}
+
+fn same_item(a: &Item, b: &Item) -> bool {
+    match (plain_item(a), plain_item(b)) {
+        (Some(a), Some(b)) => same_plain(&a, &b),
+        (a, b) => a.is_none() && b.is_none(),
+    }
+}
+
+fn same_value(a: &Value, b: &Value) -> bool {
+    same_plain(&plain_value(a), &plain_value(b))
+}
+
+fn same_table(a: &Table, b: &Table) -> bool {
+    same_plain(&plain_table(a), &plain_table(b))
+}
+
+fn same_plain(a: &toml::Value, b: &toml::Value) -> bool {
+    use toml::Value::*;
+    match (a, b) {
+        (Table(a), Table(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| same_plain(a, b)))
+        }
+        (Array(a), Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_plain(a, b))
+        }
+        // Lists of criteria can be written as a single string (see `string_or_vec`)
+        (Array(list), single @ String(_)) | (single @ String(_), Array(list)) => {
+            list.len() == 1 && same_plain(&list[0], single)
+        }
+        (a, b) => a == b,
+    }
+}
+
+/// The value of an item, without any of its formatting.
+fn plain_item(item: &Item) -> Option<toml::Value> {
+    match item {
+        Item::None => None,
+        Item::Value(value) => Some(plain_value(value)),
+        Item::Table(table) => Some(plain_table(table)),
+        Item::ArrayOfTables(tables) => Some(toml::Value::Array(
+            tables.iter().map(|table| plain_table(table)).collect(),
+        )),
+    }
+}
+
+fn plain_value(value: &Value) -> toml::Value {
+    match value {
+        Value::String(s) => toml::Value::String(s.value().clone()),
+        Value::Integer(i) => toml::Value::Integer(*i.value()),
+        Value::Float(f) => toml::Value::Float(*f.value()),
+        Value::Boolean(b) => toml::Value::Boolean(*b.value()),
+        // We don't use these, so just make sure equal ones compare equal
+        Value::Datetime(d) => toml::Value::String(d.value().to_string()),
+        Value::Array(array) => toml::Value::Array(array.iter().map(plain_value).collect()),
+        Value::InlineTable(table) => plain_table(table),
+    }
+}
+
+fn plain_table(table: &dyn TableLike) -> toml::Value {
+    toml::Value::Table(
+        table
+            .iter()
+            .filter_map(|(key, item)| Some((key.to_owned(), plain_item(item)?)))
+            .collect(),
+    )
+}
@@ -113,6 +113,15 @@ impl MetaConfig {
     }
 }
 
+/// The version of the store's file formats (config.toml, audits.toml, and
+/// imports.lock), which is recorded in each of them as `format-version`.
+/// Files from before we recorded this are version 1.
+///
+/// Bump this whenever the formats change in a way that older versions of
+/// cargo-vet would misunderstand, and teach `storage::migrate_config` how to
+/// upgrade stores from the previous version.
+pub const FORMAT_VERSION: u64 = 2;
+
 ////////////////////////////////////////////////////////////////////////////////////
 //                                                                                //
 //                                                                                //
@@ -126,7 +135,7 @@ impl MetaConfig {
 pub type AuditedDependencies = SortedMap<PackageName, Vec<AuditEntry>>;
 
 /// audits.toml
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Eq)]
 pub struct AuditsFile {
     /// A map of criteria_name to details on that criteria.
     #[serde(skip_serializing_if = "SortedMap::is_empty")]
@@ -137,7 +146,7 @@ pub struct AuditsFile {
 }
 
 /// Information on a Criteria
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct CriteriaEntry {
     /// Summary of how you evaluate something by this criteria.
     pub description: Option<String>,
@@ -160,20 +169,40 @@ pub struct AuditEntry {
     pub criteria: CriteriaName,
     #[serde(flatten)]
     pub kind: AuditKind,
+    /// The platforms this entry is restricted to, in Cargo's platform-specific
+    /// dependency syntax. `None` means it applies to all platforms.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    #[serde(with = "serialization::string_or_vec_or_none")]
+    pub targets: Option<Vec<String>>,
+    /// Where `cargo vet aggregate` found this entry
+    #[serde(rename = "aggregated-from")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub aggregated_from: Option<String>,
 }
 
 /// Implement PartialOrd manually because the order we want for sorting is
 /// different than the order we want for serialization.
 impl cmp::PartialOrd for AuditEntry {
-    fn partial_cmp<'a>(&'a self, other: &'a AuditEntry) -> Option<cmp::Ordering> {
-        let tuple = |x: &'a AuditEntry| (&x.kind, &x.criteria, &x.who, &x.notes);
-        tuple(self).partial_cmp(&tuple(other))
+    fn partial_cmp(&self, other: &AuditEntry) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
     }
 }
 
 impl cmp::Ord for AuditEntry {
-    fn cmp(&self, other: &AuditEntry) -> cmp::Ordering {
-        self.partial_cmp(other).unwrap()
+    fn cmp<'a>(&'a self, other: &'a AuditEntry) -> cmp::Ordering {
+        let tuple = |x: &'a AuditEntry| {
+            (
+                &x.kind,
+                &x.criteria,
+                &x.targets,
+                &x.who,
+                &x.notes,
+                &x.aggregated_from,
+            )
+        };
+        tuple(self).partial_cmp(&tuple(other)).unwrap()
     }
 }
 
@@ -269,7 +298,7 @@ impl Serialize for Delta {
 ////////////////////////////////////////////////////////////////////////////////////
 
 /// config.toml
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
 pub struct ConfigFile {
     /// This top-level key specifies the default criteria that cargo vet certify will use
     /// when recording audits. If unspecified, this defaults to "safe-to-deploy".
@@ -278,6 +307,21 @@ pub struct ConfigFile {
     #[serde(skip_serializing_if = "is_default_criteria")]
     pub default_criteria: CriteriaName,
 
+    /// If an import can't be fetched, keep using the copy of it in imports.lock
+    /// (with a warning) instead of failing. Also enabled by `--allow-stale-imports`.
+    #[serde(rename = "allow-stale-imports")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub allow_stale_imports: bool,
+
+    /// The target triples the project is built for. Audits restricted to
+    /// certain targets only count if they cover all of these. If unspecified,
+    /// we consider every platform.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    #[serde(with = "serialization::string_or_vec_or_none")]
+    pub targets: Option<Vec<String>>,
+
     /// Remote audits.toml's that we trust and want to import.
     #[serde(skip_serializing_if = "SortedMap::is_empty")]
     #[serde(default)]
@@ -294,6 +338,13 @@ pub struct ConfigFile {
     #[serde(skip_serializing_if = "SortedMap::is_empty")]
     #[serde(default)]
     pub unaudited: SortedMap<PackageName, Vec<UnauditedDependency>>,
+
+    /// Violations from imported audits that we've looked at and decided to live with.
+    /// These are reported as warnings instead of failing the vet.
+    #[serde(rename = "acknowledged-violations")]
+    #[serde(skip_serializing_if = "SortedMap::is_empty")]
+    #[serde(default)]
+    pub acknowledged_violations: SortedMap<PackageName, Vec<AcknowledgedViolation>>,
 }
 
 pub static SAFE_TO_DEPLOY: CriteriaStr = "safe-to-deploy";
@@ -321,7 +372,7 @@ fn is_default_criteria(val: &CriteriaName) -> bool {
 /// If this sounds overwhelming, don't worry, everything defaults to "nothing special"
 /// and an empty PolicyTable basically just means "everything should satisfy the
 /// default criteria in audits.toml".
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
 pub struct PolicyEntry {
     /// Default criteria that must be satisfied by all *direct* third-party (foreign)
     /// dependencies of first-party crates. If satisfied, the first-party crate is
@@ -339,11 +390,19 @@ pub struct PolicyEntry {
     #[serde(with = "serialization::string_or_vec_or_none")]
     pub dev_criteria: Option<Vec<CriteriaName>>,
 
-    /// TODO: figure this out
+    /// The target triples this crate is built for. Dependencies that aren't
+    /// active on any of them are left out of the graph. Defaults to the
+    /// project's `targets`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    #[serde(with = "serialization::string_or_vec_or_none")]
     pub targets: Option<Vec<String>>,
 
     /// `targets` but for dev-deps
     #[serde(rename = "dev-targets")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    #[serde(with = "serialization::string_or_vec_or_none")]
     pub dev_targets: Option<Vec<String>>,
 
     /// Freeform notes
@@ -363,17 +422,55 @@ pub static DEFAULT_POLICY_CRITERIA: CriteriaStr = SAFE_TO_DEPLOY;
 pub static DEFAULT_POLICY_DEV_CRITERIA: CriteriaStr = SAFE_TO_RUN;
 
 /// A remote audits.toml that we trust the contents of (by virtue of trusting the maintainer).
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
 pub struct RemoteImport {
-    /// URL of the foreign audits.toml
+    /// URL of the foreign audits.toml. This can also be a `file://` URL, or a path
+    /// relative to the store directory, for audits that live on the same machine.
     pub url: String,
     /// A list of criteria that are implied by foreign criteria
     #[serde(rename = "criteria-map")]
+    #[serde(default)]
     pub criteria_map: Vec<CriteriaMapping>,
+    /// Whether their built-in criteria imply ours, which they do unless this
+    /// is turned off. The built-in criteria mean the same thing everywhere, so
+    /// they don't need a `criteria-map`.
+    #[serde(rename = "map-builtins")]
+    #[serde(default = "get_default_map_builtins")]
+    #[serde(skip_serializing_if = "is_default_map_builtins")]
+    pub map_builtins: bool,
+    /// If not empty, only audits of these packages are trusted
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub include: Vec<PackageName>,
+    /// Audits of these packages aren't trusted
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub exclude: Vec<PackageName>,
+    /// If not empty, only audits by these people (their `who`) are trusted
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub auditors: Vec<String>,
+    /// The strongest of our criteria that this import can grant. Anything
+    /// that isn't implied by it is ignored.
+    #[serde(rename = "max-criteria")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub max_criteria: Option<CriteriaName>,
+    /// The sha256 (in hex) that the foreign audits.toml must have. If set, we
+    /// refuse to import anything else until it's re-pinned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+fn get_default_map_builtins() -> bool {
+    true
+}
+fn is_default_map_builtins(val: &bool) -> bool {
+    *val
 }
 
 /// Translations of foreign criteria to local criteria.
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
 pub struct CriteriaMapping {
     /// This local criteria is implied...
     pub ours: CriteriaName,
@@ -408,6 +505,17 @@ pub struct UnauditedDependency {
     pub dependency_criteria: DependencyCriteria,
 }
 
+/// A violation from an import that we've chosen to accept anyway.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct AcknowledgedViolation {
+    /// The import whose violation this is.
+    pub import: ImportName,
+    /// The version range of the violation, exactly as the import wrote it.
+    pub violation: VersionReq,
+    /// Why we're fine with using the package anyway. This is required.
+    pub justification: String,
+}
+
 static DEFAULT_UNAUDITED_SUGGEST: bool = true;
 pub fn get_default_unaudited_suggest() -> bool {
     DEFAULT_UNAUDITED_SUGGEST
@@ -426,12 +534,43 @@ fn is_default_unaudited_suggest(val: &bool) -> bool {
 //                                                                                //
 ////////////////////////////////////////////////////////////////////////////////////
 
-/// imports.lock, not sure what I want to put in here yet.
-#[derive(serde::Serialize, serde::Deserialize)]
+/// imports.lock, the last-fetched contents of each import.
+///
+/// Along with the audits, this records the definitions of the foreign criteria
+/// we've accepted, so we can tell when an import changes what they mean.
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
 pub struct ImportsFile {
+    /// When we fetched the copy of each import below, in seconds since the
+    /// unix epoch, so we can say how stale it is if we have to fall back to it.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "SortedMap::is_empty")]
+    pub fetched: SortedMap<ImportName, u64>,
     pub audits: SortedMap<ImportName, AuditsFile>,
 }
 
+////////////////////////////////////////////////////////////////////////////////////
+//                                                                                //
+//                                                                                //
+//                                                                                //
+//                               registry.toml                                    //
+//                                                                                //
+//                                                                                //
+//                                                                                //
+////////////////////////////////////////////////////////////////////////////////////
+
+/// registry.toml, a list of well-known audit sets for `cargo vet import`.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct RegistryFile {
+    pub registry: SortedMap<ImportName, RegistryEntry>,
+}
+
+/// An audit set in the registry.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct RegistryEntry {
+    /// URL of the audit set's audits.toml
+    pub url: String,
+}
+
 ////////////////////////////////////////////////////////////////////////////////////
 //                                                                                //
 //                                                                                //
@@ -474,7 +613,7 @@ pub enum FetchCommand {
 }
 
 impl FetchCommand {
-    pub fn package(&self) -> PackageStr {
+    pub fn package(&self) -> PackageStr<'_> {
         match self {
             FetchCommand::Inspect { package, .. } => package,
             FetchCommand::Diff { package, .. } => package,
@@ -5,7 +5,12 @@ use std::ops::Deref;
 use std::panic::panic_any;
 use std::path::Path;
 use std::process::Command;
-use std::{fs::File, io::Write, panic, path::PathBuf};
+use std::{
+    fs::File,
+    io::{self, IsTerminal, Write},
+    panic,
+    path::PathBuf,
+};
 
 use cargo_metadata::{Metadata, Package};
 use clap::{CommandFactory, Parser};
@@ -17,18 +22,21 @@ use tracing::{error, info, trace, warn};
 use crate::cli::*;
 use crate::format::{
     AuditEntry, AuditKind, AuditsFile, ConfigFile, CriteriaEntry, CriteriaStr, Delta,
-    DependencyCriteria, DiffStat, FetchCommand, ImportsFile, MetaConfig, MetaConfigInstance,
-    PackageStr, SortedMap, StoreInfo, UnauditedDependency,
+    DependencyCriteria, DiffStat, FastSet, FetchCommand, ImportsFile, MetaConfig,
+    MetaConfigInstance, PackageStr, RemoteImport, SortedMap, StoreInfo, UnauditedDependency,
+    FORMAT_VERSION, SAFE_TO_DEPLOY, SAFE_TO_RUN,
 };
-use crate::resolver::{Conclusion, CriteriaMapper, DepGraph, SuggestItem};
-use crate::storage::{Cache, Store};
+use crate::resolver::{Conclusion, CriteriaMapper, DepGraph, RegistrySource, SuggestItem};
+use crate::storage::{Cache, Store, DEFAULT_REGISTRY};
 
 mod cli;
+mod editor;
 mod flock;
 pub mod format;
 pub mod resolver;
 mod serialization;
 pub mod storage;
+mod targets;
 #[cfg(test)]
 mod tests;
 
@@ -108,7 +116,7 @@ fn main() -> Result<(), VetError> {
                 #[cfg(target_family = "unix")]
                 {
                     use std::os::unix::process::CommandExt;
-                    _command.exec();
+                    let _ = _command.exec();
                 }
                 unreachable!("we only use ExecPanic for unix");
             } else {
@@ -205,6 +213,7 @@ fn real_main() -> Result<(), VetError> {
     match &partial_cfg.cli.command {
         Some(Inspect(sub_args)) => return cmd_inspect(out, &partial_cfg, sub_args),
         Some(Diff(sub_args)) => return cmd_diff(out, &partial_cfg, sub_args),
+        Some(Aggregate(sub_args)) => return cmd_aggregate(out, &partial_cfg, sub_args),
         Some(HelpMarkdown(sub_args)) => return cmd_help_md(out, &partial_cfg, sub_args),
         _ => {
             // Not a freestanding command, time to do full parsing and setup
@@ -354,7 +363,11 @@ fn real_main() -> Result<(), VetError> {
         Some(AddUnaudited(sub_args)) => cmd_add_unaudited(out, &cfg, sub_args),
         Some(RecordViolation(sub_args)) => cmd_record_violation(out, &cfg, sub_args),
         Some(Suggest(sub_args)) => cmd_suggest(out, &cfg, sub_args),
+        Some(Explain(sub_args)) => cmd_explain(out, &cfg, sub_args),
+        Some(Why(sub_args)) => cmd_why(out, &cfg, sub_args),
         Some(Fmt(sub_args)) => cmd_fmt(out, &cfg, sub_args),
+        Some(Migrate(sub_args)) => cmd_migrate(out, &cfg, sub_args),
+        Some(Import(sub_args)) => cmd_import(out, &cfg, sub_args),
         Some(FetchImports(sub_args)) => cmd_fetch_imports(out, &cfg, sub_args),
         Some(RegenerateUnaudited(sub_args)) => cmd_regenerate_unaudited(out, &cfg, sub_args),
         Some(DumpGraph(sub_args)) => cmd_dump_graph(out, &cfg, sub_args),
@@ -391,13 +404,14 @@ pub fn init_files(
 
     // Default imports file is empty
     let imports = ImportsFile {
+        fetched: SortedMap::new(),
         audits: SortedMap::new(),
     };
 
     // This is the hard one
     let config = {
         let mut dependencies = SortedMap::new();
-        let graph = DepGraph::new(metadata, filter_graph);
+        let graph = DepGraph::new(metadata, filter_graph, None)?;
         for package in &graph.nodes {
             if !package.is_third_party {
                 // Only care about third-party packages
@@ -423,9 +437,12 @@ pub fn init_files(
         }
         ConfigFile {
             default_criteria: format::get_default_criteria(),
+            allow_stale_imports: false,
+            targets: None,
             imports: SortedMap::new(),
             unaudited: dependencies,
             policy: SortedMap::new(),
+            acknowledged_violations: SortedMap::new(),
         }
     };
 
@@ -522,7 +539,7 @@ fn cmd_certify(out: &mut dyn Write, cfg: &Config, sub_args: &CertifyArgs) -> Res
         for arg in &sub_args.dependency_criteria {
             dep_criteria
                 .entry(arg.dependency.clone())
-                .or_insert_with(Vec::new)
+                .or_default()
                 .push(arg.criteria.clone());
         }
         dep_criteria
@@ -846,6 +863,8 @@ fn cmd_certify(out: &mut dyn Write, cfg: &Config, sub_args: &CertifyArgs) -> Res
             criteria: criteria.to_string(),
             who: who.clone(),
             notes: notes.clone(),
+            targets: None,
+            aggregated_from: None,
         };
 
         store
@@ -933,6 +952,8 @@ fn cmd_record_violation(
         criteria,
         who,
         notes,
+        targets: None,
+        aggregated_from: None,
     };
 
     store
@@ -963,7 +984,7 @@ fn cmd_add_unaudited(
         for arg in &sub_args.dependency_criteria {
             dep_criteria
                 .entry(arg.dependency.clone())
-                .or_insert_with(Vec::new)
+                .or_default()
                 .push(arg.criteria.clone());
         }
         dep_criteria
@@ -1022,7 +1043,13 @@ fn cmd_add_unaudited(
 fn cmd_suggest(out: &mut dyn Write, cfg: &Config, sub_args: &SuggestArgs) -> Result<(), VetError> {
     // Run the checker to validate that the current set of deps is covered by the current cargo vet store
     trace!("suggesting...");
-    let mut store = Store::acquire(cfg)?;
+    if sub_args.registry.is_some() && cfg.cli.locked {
+        // ERRORS: immediate fatal diagnostic
+        return Err(eyre!(
+            "can't check the registry with --locked, as that requires fetching audit sets"
+        ));
+    }
+    let mut store = Store::acquire_ro(cfg)?;
 
     // Delete all unaudited entries except those that are suggest=false
     for versions in &mut store.config.unaudited.values_mut() {
@@ -1035,10 +1062,17 @@ fn cmd_suggest(out: &mut dyn Write, cfg: &Config, sub_args: &SuggestArgs) -> Res
         cfg.cli.filter_graph.as_ref(),
         &store,
         sub_args.guess_deeper,
-    );
+    )?;
+    // Checking the registry for audit sets to import requires the network,
+    // so it's only done when asked for (and not with --locked, see above).
+    let registry = sub_args.registry.as_ref().map(|registry| RegistrySource {
+        registry: registry.as_deref().unwrap_or(DEFAULT_REGISTRY),
+        store: &store,
+        guess_deeper: sub_args.guess_deeper,
+    });
     match cfg.cli.output_format {
-        OutputFormat::Human => report.print_suggest_human(out, cfg)?,
-        OutputFormat::Json => report.print_json(out, cfg)?,
+        OutputFormat::Human => report.print_suggest_human(out, cfg, registry.as_ref())?,
+        OutputFormat::Json => report.print_suggest_json(out, cfg, registry.as_ref())?,
     }
 
     // Don't commit the store, because we purged the unaudited table above.
@@ -1046,6 +1080,62 @@ fn cmd_suggest(out: &mut dyn Write, cfg: &Config, sub_args: &SuggestArgs) -> Res
     Ok(())
 }
 
+fn cmd_explain(out: &mut dyn Write, cfg: &Config, sub_args: &ExplainArgs) -> Result<(), VetError> {
+    // Resolve as usual, and then dig into the results for the one package
+    trace!("explaining...");
+    let store = Store::acquire_ro(cfg)?;
+
+    let report = resolver::resolve(&cfg.metadata, cfg.cli.filter_graph.as_ref(), &store, false)?;
+    let pkgidx = report
+        .graph
+        .nodes
+        .iter()
+        .position(|package| {
+            package.name == sub_args.package && package.version == &sub_args.version
+        })
+        .ok_or_else(|| {
+            // ERRORS: immediate fatal diagnostic
+            eyre!(
+                "{}:{} is not in the dependency graph",
+                sub_args.package,
+                sub_args.version
+            )
+        })?;
+    match cfg.cli.output_format {
+        OutputFormat::Human => report.print_explain_human(out, pkgidx)?,
+        OutputFormat::Json => report.print_explain_json(out, pkgidx)?,
+    }
+
+    Ok(())
+}
+
+fn cmd_why(out: &mut dyn Write, cfg: &Config, sub_args: &WhyArgs) -> Result<(), VetError> {
+    // Resolve as usual, and then walk down from the roots to the package
+    trace!("explaining why...");
+    let store = Store::acquire_ro(cfg)?;
+
+    let report = resolver::resolve(&cfg.metadata, cfg.cli.filter_graph.as_ref(), &store, false)?;
+    let targets = report
+        .graph
+        .nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, package)| package.name == sub_args.package)
+        .map(|(pkgidx, _)| pkgidx)
+        .collect::<Vec<_>>();
+    if targets.is_empty() {
+        // ERRORS: immediate fatal diagnostic
+        return Err(eyre!("{} is not in the dependency graph", sub_args.package));
+    }
+    let paths = report.compute_why(&store, &targets);
+    match cfg.cli.output_format {
+        OutputFormat::Human => report.print_why_human(out, &sub_args.package, &paths)?,
+        OutputFormat::Json => report.print_why_json(out, &sub_args.package, &paths)?,
+    }
+
+    Ok(())
+}
+
 fn cmd_regenerate_unaudited(
     _out: &mut dyn Write,
     cfg: &Config,
@@ -1053,7 +1143,17 @@ fn cmd_regenerate_unaudited(
 ) -> Result<(), VetError> {
     // Run the checker to validate that the current set of deps is covered by the current cargo vet store
     trace!("regenerating unaudited...");
-    let mut store = Store::acquire(cfg)?;
+    // Entries for packages we no longer depend on would fail validation, but cleaning
+    // those up is part of what we're here to do, so drop them before validating.
+    let mut store = Store::acquire_unvalidated(cfg)?;
+    let third_party = foreign_packages(&cfg.metadata)
+        .map(|package| &*package.name)
+        .collect::<FastSet<_>>();
+    store
+        .config
+        .unaudited
+        .retain(|package, _| third_party.contains(&**package));
+    store.validate(Some(&cfg.metadata))?;
 
     minimize_unaudited(cfg, &mut store)?;
 
@@ -1068,7 +1168,7 @@ pub fn minimize_unaudited(cfg: &Config, store: &mut Store) -> Result<(), VetErro
     let old_unaudited = mem::take(&mut store.config.unaudited);
 
     // Try to vet
-    let report = resolver::resolve(&cfg.metadata, cfg.cli.filter_graph.as_ref(), store, true);
+    let report = resolver::resolve(&cfg.metadata, cfg.cli.filter_graph.as_ref(), store, true)?;
 
     trace!("minimizing unaudited...");
     let new_unaudited = if let Some(suggest) = report.compute_suggest(cfg, false)? {
@@ -1190,17 +1290,31 @@ fn cmd_diff(out: &mut dyn Write, cfg: &PartialConfig, sub_args: &DiffArgs) -> Re
     Ok(())
 }
 
+fn cmd_aggregate(
+    out: &mut dyn Write,
+    cfg: &PartialConfig,
+    sub_args: &AggregateArgs,
+) -> Result<(), VetError> {
+    let audits = storage::aggregate(cfg, &sub_args.sources)?;
+    storage::write_audits(out, audits)?;
+    Ok(())
+}
+
 fn cmd_vet(out: &mut dyn Write, cfg: &Config) -> Result<(), VetError> {
     // Run the checker to validate that the current set of deps is covered by the current cargo vet store
     trace!("vetting...");
 
-    let mut store = Store::acquire(cfg)?;
-    if !cfg.cli.locked {
-        store.fetch_foreign_audits()?;
-    }
+    // If we're --locked we won't be changing anything, so we can share the store.
+    let store = if cfg.cli.locked {
+        Store::acquire_ro(cfg)?
+    } else {
+        let mut store = Store::acquire(cfg)?;
+        store.fetch_foreign_audits(cfg)?;
+        store
+    };
 
     // DO THE THING!!!!
-    let report = resolver::resolve(&cfg.metadata, cfg.cli.filter_graph.as_ref(), &store, false);
+    let report = resolver::resolve(&cfg.metadata, cfg.cli.filter_graph.as_ref(), &store, false)?;
     match cfg.cli.output_format {
         OutputFormat::Human => report.print_human(out, cfg)?,
         OutputFormat::Json => report.print_json(out, cfg)?,
@@ -1211,23 +1325,116 @@ fn cmd_vet(out: &mut dyn Write, cfg: &Config) -> Result<(), VetError> {
         // ERRORS: immediate fatal diagnostic? Arguably should be silent.
         // Err(eyre!("report contains errors"))?;
         panic_any(ExitPanic(-1));
-    } else {
+    } else if !cfg.cli.locked {
         store.commit()?;
     }
 
     Ok(())
 }
 
+fn cmd_import(out: &mut dyn Write, cfg: &Config, sub_args: &ImportArgs) -> Result<(), VetError> {
+    // Add an import from the registry
+    trace!("importing...");
+
+    if cfg.cli.locked {
+        // ERRORS: immediate fatal diagnostic
+        return Err(eyre!(
+            "can't import with --locked, as that requires fetching the import"
+        ));
+    }
+
+    let mut store = Store::acquire(cfg)?;
+    let name = &sub_args.name;
+    if store.config.imports.contains_key(name) {
+        // ERRORS: immediate fatal diagnostic
+        return Err(eyre!("`{name}` is already imported"));
+    }
+
+    let registry_url = sub_args.registry.as_deref().unwrap_or(DEFAULT_REGISTRY);
+    let mut registry = storage::fetch_registry(cfg, registry_url)?;
+    let entry = match registry.registry.remove(name) {
+        Some(entry) => entry,
+        None => {
+            let known = registry
+                .registry
+                .keys()
+                .map(|name| format!("`{name}`"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            // ERRORS: immediate fatal diagnostic
+            return Err(eyre!(
+                "`{name}` isn't in the registry @ {registry_url} (it has: {known})"
+            ));
+        }
+    };
+
+    // The built-in criteria mean the same thing everywhere, so mapping them is
+    // almost always what you want, but it's still your call to trust them.
+    let map_builtins = if sub_args.map_builtins || sub_args.no_map_builtins {
+        sub_args.map_builtins
+    } else {
+        // Ask on the terminal, as `out` may be a file
+        let term = Term::stderr();
+        if !term.is_term() || !io::stdin().is_terminal() {
+            // ERRORS: immediate fatal diagnostic
+            return Err(eyre!(
+                "can't ask whether to map {name}'s built-in criteria ({SAFE_TO_DEPLOY}, {SAFE_TO_RUN}) to your own without a terminal, pass --map-builtins or --no-map-builtins"
+            ));
+        }
+        term.write_str(&format!(
+            "Map {name}'s built-in criteria ({SAFE_TO_DEPLOY}, {SAFE_TO_RUN}) to your own? (y/n): "
+        ))?;
+        let answer = term.read_line()?.trim().to_lowercase();
+        answer == "y" || answer == "yes"
+    };
+
+    store.config.imports.insert(
+        name.clone(),
+        RemoteImport {
+            url: entry.url.clone(),
+            criteria_map: vec![],
+            map_builtins,
+            include: vec![],
+            exclude: vec![],
+            auditors: vec![],
+            max_criteria: None,
+            sha256: None,
+        },
+    );
+    store.fetch_foreign_audits(cfg)?;
+    store.commit()?;
+
+    writeln!(out, "Imported {name} from {}", entry.url)?;
+
+    Ok(())
+}
+
 fn cmd_fetch_imports(
     out: &mut dyn Write,
     cfg: &Config,
-    _sub_args: &FetchImportsArgs,
+    sub_args: &FetchImportsArgs,
 ) -> Result<(), VetError> {
     trace!("fetching imports...");
 
     let mut store = Store::acquire(cfg)?;
-    if !cfg.cli.locked {
-        store.fetch_foreign_audits()?;
+    if cfg.cli.locked && sub_args.pin {
+        // ERRORS: immediate fatal diagnostic
+        return Err(eyre!(
+            "can't pin imports with --locked, as that requires fetching them"
+        ));
+    } else if sub_args.pin {
+        for (name, old_pin) in store.pin_imports(cfg)? {
+            let new_pin = store.config.imports[&name]
+                .sha256
+                .as_deref()
+                .unwrap_or_default();
+            match old_pin {
+                Some(old_pin) => writeln!(out, "Re-pinned {name}: {old_pin} -> {new_pin}")?,
+                None => writeln!(out, "Pinned {name}: {new_pin}")?,
+            }
+        }
+    } else if !cfg.cli.locked {
+        store.fetch_foreign_audits(cfg)?;
     } else {
         // ERRORS: just a warning that you're holding it wrong, unclear if immediate or buffered,
         // or if this should be a hard error, or if we should ignore the --locked flag and
@@ -1250,7 +1457,7 @@ fn cmd_dump_graph(
     // Dump a mermaid-js graph
     trace!("dumping...");
 
-    let graph = resolver::DepGraph::new(&cfg.metadata, cfg.cli.filter_graph.as_ref());
+    let graph = resolver::DepGraph::new(&cfg.metadata, cfg.cli.filter_graph.as_ref(), None)?;
     match cfg.cli.output_format {
         OutputFormat::Human => graph.print_mermaid(out, sub_args)?,
         OutputFormat::Json => serde_json::to_writer_pretty(out, &graph.nodes)?,
@@ -1260,22 +1467,60 @@ fn cmd_dump_graph(
 }
 
 fn cmd_fmt(_out: &mut dyn Write, cfg: &Config, _sub_args: &FmtArgs) -> Result<(), VetError> {
-    // Reformat all the files (just load and store them, formatting is implict).
+    // Reformat all the files (just load and store them, keeping only the comments).
     trace!("formatting...");
     let store = Store::acquire(cfg)?;
+    store.commit_reformatted()?;
+    Ok(())
+}
+
+fn cmd_migrate(out: &mut dyn Write, cfg: &Config, _sub_args: &MigrateArgs) -> Result<(), VetError> {
+    // Upgrade the store to the current format (loading it does the actual work).
+    trace!("migrating...");
+    let store = Store::acquire(cfg)?;
+    match store.migrated_from() {
+        Some(version) => writeln!(
+            out,
+            "Migrated the store from format version {version} to {FORMAT_VERSION}"
+        )?,
+        None => writeln!(
+            out,
+            "The store is already in the current format (version {FORMAT_VERSION})"
+        )?,
+    }
     store.commit()?;
     Ok(())
 }
 
 fn cmd_accept_criteria_change(
-    _out: &mut dyn Write,
-    _cfg: &Config,
+    out: &mut dyn Write,
+    cfg: &Config,
     _sub_args: &AcceptCriteriaChangeArgs,
 ) -> Result<(), VetError> {
     // Accept changes that a foreign audits.toml made to their criteria.
     trace!("accepting...");
 
-    unimplemented!("TODO(#68): unimplemented feature!");
+    if cfg.cli.locked {
+        // ERRORS: immediate fatal diagnostic
+        return Err(eyre!(
+            "can't accept criteria changes with --locked, as that requires fetching imports"
+        ));
+    }
+
+    let mut store = Store::acquire(cfg)?;
+    let changes = store.accept_criteria_changes(cfg)?;
+    if changes.is_empty() {
+        writeln!(out, "No imported criteria have changed")?;
+    } else {
+        for change in &changes {
+            writeln!(out, "{change}")?;
+        }
+        writeln!(out)?;
+        writeln!(out, "Accepted {} criteria changes", changes.len())?;
+    }
+    store.commit()?;
+
+    Ok(())
 }
 
 /// Perform crimes on clap long_help to generate markdown docs
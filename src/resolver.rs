@@ -6,13 +6,15 @@ use std::io::Write;
 use tracing::{error, trace, trace_span, warn};
 
 use crate::format::{
-    self, AuditKind, CriteriaName, CriteriaStr, Delta, DiffStat, FetchCommand, ImportName,
-    PackageStr, SuggestedAudit,
+    self, AcknowledgedViolation, AuditKind, CriteriaName, CriteriaStr, Delta, DependencyCriteria,
+    DiffStat, FetchCommand, ImportName, PackageStr, RemoteImport, SuggestedAudit, SAFE_TO_DEPLOY,
+    SAFE_TO_RUN,
 };
 use crate::format::{FastMap, FastSet, SortedMap, SortedSet};
+use crate::targets::TargetSet;
 use crate::{
-    AuditEntry, Cache, Config, CriteriaEntry, DumpGraphArgs, GraphFilter, GraphFilterProperty,
-    GraphFilterQuery, PackageExt, Store, VetError,
+    storage, AuditEntry, Cache, Config, ConfigFile, CriteriaEntry, DumpGraphArgs, GraphFilter,
+    GraphFilterProperty, GraphFilterQuery, PackageExt, Store, UnauditedDependency, VetError,
 };
 
 /// A report of the results of running `resolve`.
@@ -31,6 +33,14 @@ pub struct ResolveReport<'a> {
 
     /// The final conclusion of our analysis.
     pub conclusion: Conclusion,
+
+    /// How many imported audits of our dependencies we ignored, because they
+    /// were outside the scope their import is trusted for.
+    pub filtered_foreign_audits: usize,
+
+    /// Violations from imports that conflict with our packages, but that
+    /// we've acknowledged in config.toml. These are only warned about.
+    pub acknowledged_violations: SortedMap<PackageIdx, Vec<AcknowledgedConflicts>>,
 }
 
 #[derive(Debug, Clone)]
@@ -57,11 +67,23 @@ pub struct FailForViolationConflict {
     pub violations: SortedMap<PackageIdx, Vec<ViolationConflict>>,
 }
 
+/// Conflicts with a foreign violation that we've acknowledged in config.toml.
+#[derive(Debug, Clone)]
+pub struct AcknowledgedConflicts {
+    pub conflicts: Vec<ViolationConflict>,
+    pub justification: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct FailForVet {
     /// These packages are to blame and need to be fixed
     pub failures: SortedMap<PackageIdx, AuditFailure>,
     pub suggest: Option<Suggest>,
+    /// The policy failures `failures` were blamed from, so suggest can blame
+    /// them again with better information.
+    pub root_failures: RootFailures,
+    /// Whether we blamed with `--guess-deeper`
+    pub guess_deeper: bool,
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -87,9 +109,17 @@ pub enum AuditSource {
 
 #[derive(Debug, Clone, Default)]
 pub struct Suggest {
+    /// The packages to blame, picking the cheapest dependencies to fix where
+    /// there's a choice. This can differ from [`FailForVet::failures`].
+    pub failures: SortedMap<PackageIdx, AuditFailure>,
     pub suggestions: Vec<SuggestItem>,
+    /// The failures we couldn't suggest any audits for, because fetching or
+    /// diffing the package failed.
+    pub unsuggested: Vec<PackageIdx>,
     pub suggestions_by_criteria: SortedMap<CriteriaName, Vec<SuggestItem>>,
     pub total_lines: u64,
+    /// Audit sets from the registry that would vet some of the failures
+    pub imports: Vec<ImportSuggestion>,
 }
 
 #[derive(Debug, Clone)]
@@ -98,6 +128,31 @@ pub struct SuggestItem {
     pub suggested_criteria: CriteriaSet,
     pub suggested_diff: DiffRecommendation,
     pub notable_parents: String,
+    /// How many audits we suggest for this package. This is more than one when
+    /// the criteria it failed can't be fixed with one audit as cheaply.
+    pub audits_for_package: usize,
+}
+
+/// An audit set in the registry that we don't import yet, and would vet some
+/// of the packages that are currently failing.
+#[derive(Debug, Clone)]
+pub struct ImportSuggestion {
+    pub name: ImportName,
+    pub url: String,
+    /// The failing packages that importing it would vet
+    pub vetted: Vec<PackageIdx>,
+    /// How many lines of suggested audits that would save
+    pub lines: u64,
+}
+
+/// Where `suggest` should look for audit sets to import, and how to evaluate them.
+pub struct RegistrySource<'a> {
+    /// The registry.toml to look in (a URL or a path)
+    pub registry: &'a str,
+    /// The store we resolved against, which candidate imports are added to
+    pub store: &'a Store,
+    /// Whether we resolved with `--guess-deeper`
+    pub guess_deeper: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -107,10 +162,72 @@ pub struct DiffRecommendation {
     pub diffstat: DiffStat,
 }
 
-/// Set of booleans, 64 should be Enough For Anyone (but abstracting in case not).
+/// The most paths `compute_why` will find.
+pub const MAX_WHY_PATHS: usize = 100;
+
+/// The paths to a package found by `compute_why`.
+#[derive(Debug, Clone)]
+pub struct DependencyPaths {
+    pub paths: Vec<DependencyPath>,
+    /// Whether there were more than [`MAX_WHY_PATHS`][], and the rest were
+    /// left out.
+    pub truncated: bool,
+}
+
+/// A path through the dependency graph from a root (or the dev-build of a
+/// workspace member) down to a package, as computed by `compute_why`.
+#[derive(Debug, Clone)]
+pub struct DependencyPath {
+    /// Whether this path starts at the dev-build of a workspace member
+    pub dev: bool,
+    /// The packages along the path, starting at the root
+    pub steps: Vec<DependencyStep>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DependencyStep {
+    pub package: PackageIdx,
+    /// The criteria this package needs to satisfy on this path
+    pub criteria: CriteriaSet,
+    /// Where that requirement came from
+    pub reason: RequirementReason,
+}
+
+/// Why a package on a [`DependencyPath`] needs its criteria.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequirementReason {
+    /// The `criteria` (or `dev-criteria`) of the root's own policy
+    Policy,
+    /// The default policy for roots (or dev-builds)
+    DefaultPolicy,
+    /// The same criteria as its parent
+    Inherited,
+    /// A `dependency-criteria` in the policy of its parent
+    PolicyDependencyCriteria,
+    /// A `dependency-criteria` of the audits that vet its parent
+    AuditDependencyCriteria,
+}
+
+/// Set of booleans, one per criteria.
+///
+/// Up to 64 criteria (the common case) fit inline in a single word, which is
+/// cheap to copy around. Beyond that the bits spill over to the heap.
 #[derive(Clone, Default)]
-pub struct CriteriaSet(u64);
-const MAX_CRITERIA: usize = u64::BITS as usize; // funnier this way
+pub struct CriteriaSet(CriteriaBits);
+
+#[derive(Clone)]
+enum CriteriaBits {
+    Inline(u64),
+    Heap(Box<[u64]>),
+}
+
+impl Default for CriteriaBits {
+    fn default() -> Self {
+        CriteriaBits::Inline(0)
+    }
+}
+
+const WORD_BITS: usize = u64::BITS as usize;
 
 /// A processed version of config.toml's criteria definitions, for mapping
 /// lists of criteria names to CriteriaSets.
@@ -149,6 +266,86 @@ pub struct PackageNode<'a> {
     pub is_root: bool,
     /// Whether this package only shows up in dev (test/bench) builds
     pub is_dev_only: bool,
+    /// The targets this package is built for
+    #[serde(skip)]
+    pub targets: TargetSet,
+}
+
+/// The targets that each package is built for.
+type PackageTargets<'a> = FastMap<&'a PackageId, TargetSet>;
+
+/// Work out which targets each package is built for. The workspace members are
+/// built for the `targets` and `dev-targets` of their policies (or the
+/// project's `targets`), and those are pushed down the dependency edges that
+/// are active on them.
+///
+/// Returns the targets of each package's normal build, and of each workspace
+/// member's dev build. The cfgs of every target are looked up here, so this
+/// fails if rustc can't give us them, and nothing later that matches against
+/// the targets (like [`entry_applies_to_targets`][]) has to.
+fn compute_targets<'a>(
+    metadata: &'a Metadata,
+    resolve_index_by_pkgid: &SortedMap<&'a PackageId, usize>,
+    config: &ConfigFile,
+) -> Result<(PackageTargets<'a>, PackageTargets<'a>), VetError> {
+    let resolve_list = &metadata.resolve.as_ref().unwrap().nodes;
+    let project_targets = TargetSet::new(config.targets.as_deref())?;
+    let mut targets = PackageTargets::new();
+    let mut dev_targets = PackageTargets::new();
+    let mut queue = vec![];
+
+    fn add<'a>(
+        targets: &mut PackageTargets<'a>,
+        queue: &mut Vec<&'a PackageId>,
+        pkgid: &'a PackageId,
+        new: TargetSet,
+    ) {
+        let cur = targets.entry(pkgid).or_insert_with(TargetSet::none);
+        if cur.union_with(&new) {
+            queue.push(pkgid);
+        }
+    }
+
+    for pkgid in &metadata.workspace_members {
+        let policy = config.policy.get(&metadata[pkgid].name);
+        let policy_targets = |targets: Option<&Vec<String>>| match targets {
+            Some(targets) => TargetSet::new(Some(targets)),
+            None => Ok(project_targets.clone()),
+        };
+        let normal = policy_targets(policy.and_then(|policy| policy.targets.as_ref()))?;
+        let dev = policy_targets(policy.and_then(|policy| policy.dev_targets.as_ref()))?;
+
+        let resolve_node = &resolve_list[resolve_index_by_pkgid[pkgid]];
+        for dep in &resolve_node.deps {
+            for dep_kind in &dep.dep_kinds {
+                if dep_kind.kind == DependencyKind::Development {
+                    let active = dev.restricted_to(dep_kind.target.as_ref());
+                    add(&mut targets, &mut queue, &dep.pkg, active);
+                }
+            }
+        }
+        add(&mut targets, &mut queue, pkgid, normal);
+        dev_targets.insert(pkgid, dev);
+    }
+
+    while let Some(pkgid) = queue.pop() {
+        let cur = targets[pkgid].clone();
+        let resolve_node = &resolve_list[resolve_index_by_pkgid[pkgid]];
+        for dep in &resolve_node.deps {
+            for dep_kind in &dep.dep_kinds {
+                let active = cur.restricted_to(dep_kind.target.as_ref());
+                let active = match dep_kind.kind {
+                    DependencyKind::Normal => active,
+                    // Build dependencies are built for the host, which could be anything
+                    DependencyKind::Build if !active.is_empty() => TargetSet::all(),
+                    _ => continue,
+                };
+                add(&mut targets, &mut queue, &dep.pkg, active);
+            }
+        }
+    }
+
+    Ok((targets, dev_targets))
 }
 
 /// Don't serialize path package ids, not stable across systems
@@ -181,6 +378,8 @@ pub struct ResolveResult<'a> {
 }
 
 pub type PolicyFailures = SortedMap<PackageIdx, CriteriaSet>;
+/// Dependencies that don't meet the criteria something required of them.
+pub type FailedDeps = SortedMap<PackageIdx, CriteriaSet>;
 /// (FailedPackage, Failures, is_dev)
 pub type RootFailures = Vec<(PackageIdx, PolicyFailures, bool)>;
 
@@ -196,16 +395,19 @@ pub enum SearchResult<'a> {
     Connected {
         /// Whether we found a path to a fully_audited entry
         fully_audited: bool,
+        /// The chain of audits we found, from 0.0.0 up to the version. Empty for
+        /// first-party packages, which are vetted through their dependencies.
+        path: Vec<AuditStep<'a>>,
     },
     /// We failed to find a *proper* path, criteria not valid, but adding in failing
     /// edges caused by our dependencies not meeting criteria created a connection!
     /// If you fix these dependencies then we should validate this criteria!
     PossiblyConnected {
-        /// The dependencies that failed on some edges (blame them).
-        /// This is currently overbroad in corner cases where there are two possible
-        /// paths blocked by two different dependencies and so only fixing one would
-        /// actually be sufficient, but, whatever.
-        failed_deps: SortedMap<PackageIdx, CriteriaSet>,
+        /// The dependencies to blame, as alternatives: fixing all the dependencies
+        /// in any one of these would validate this criteria. Each alternative is
+        /// minimal, and they're sorted with the smallest first. Only the smallest
+        /// few are kept when there are many, see `minimal_failed_deps`.
+        alternatives: Vec<FailedDeps>,
     },
     /// We failed to find any path, criteria not valid.
     Disconnected {
@@ -231,9 +433,81 @@ pub struct DeltaEdge<'a> {
     /// Requirements that dependencies must satisfy for the edge to be valid.
     /// If a dependency isn't mentionned, then it defaults to `criteria`.
     dependency_criteria: FastMap<PackageStr<'a>, CriteriaSet>,
-    /// Whether this edge represents an 'unaudited' entry. These will initially
-    /// be ignored, and then used only if we can't find a path.
-    is_unaudited_entry: bool,
+    /// The entry this edge came from. 'unaudited' entries will initially be
+    /// ignored, and then used only if we can't find a path.
+    origin: DeltaOrigin<'a>,
+}
+
+/// Where an edge in the graph of audits came from.
+#[derive(Debug, Clone, Copy)]
+pub enum DeltaOrigin<'a> {
+    /// An entry in our own audits.toml
+    OwnAudit(&'a AuditEntry),
+    /// An entry in the audits.toml of the named import
+    ForeignAudit(&'a str, &'a AuditEntry),
+    /// An 'unaudited' entry in config.toml
+    Unaudited(&'a UnauditedDependency),
+}
+
+/// One step of an audit chain. Full audits and 'unaudited' entries are steps
+/// from 0.0.0.
+#[derive(Debug, Clone)]
+pub struct AuditStep<'a> {
+    pub from: &'a Version,
+    pub to: &'a Version,
+    pub origin: DeltaOrigin<'a>,
+}
+
+impl<'a> AuditStep<'a> {
+    /// What the entry behind this step requires of the dependency `dep_name`
+    /// (mapped to our criteria), if it says anything about it.
+    ///
+    /// This is looked up on demand rather than stored, as only a few steps ever
+    /// need it.
+    fn dependency_criteria(
+        &self,
+        store: &Store,
+        criteria_mapper: &CriteriaMapper,
+        dep_name: PackageStr,
+    ) -> Option<CriteriaSet> {
+        match self.origin {
+            DeltaOrigin::OwnAudit(entry) => entry_dependency_criteria(entry)?
+                .get(dep_name)
+                .map(|criteria| criteria_mapper.criteria_from_list(criteria)),
+            DeltaOrigin::ForeignAudit(import, entry) => {
+                let criteria = entry_dependency_criteria(entry)?.get(dep_name)?;
+                let foreign_criteria_mapper =
+                    CriteriaMapper::new(&store.imports.audits[import].criteria);
+                let criteria_map =
+                    import_criteria_map(&store.config.imports[import], &foreign_criteria_mapper);
+                map_foreign_requirement(
+                    criteria_mapper,
+                    &foreign_criteria_mapper,
+                    &criteria_map,
+                    &foreign_criteria_mapper.criteria_from_list(criteria),
+                )
+            }
+            DeltaOrigin::Unaudited(allowed) => allowed
+                .dependency_criteria
+                .get(dep_name)
+                .map(|criteria| criteria_mapper.criteria_from_list(criteria)),
+        }
+    }
+}
+
+/// The `dependency-criteria` of an audit, if it's the kind that has them.
+fn entry_dependency_criteria(entry: &AuditEntry) -> Option<&DependencyCriteria> {
+    match &entry.kind {
+        AuditKind::Full {
+            dependency_criteria,
+            ..
+        }
+        | AuditKind::Delta {
+            dependency_criteria,
+            ..
+        } => Some(dependency_criteria),
+        AuditKind::Violation { .. } => None,
+    }
 }
 
 fn builtin_criteria() -> SortedMap<CriteriaName, CriteriaEntry> {
@@ -362,63 +636,136 @@ impl CriteriaMapper {
 
 impl CriteriaSet {
     pub fn none(count: usize) -> Self {
-        assert!(
-            count <= MAX_CRITERIA,
-            "{MAX_CRITERIA} was not Enough For Everyone ({count} criteria)"
-        );
-        CriteriaSet(0)
+        if count <= WORD_BITS {
+            CriteriaSet(CriteriaBits::Inline(0))
+        } else {
+            let words = count.div_ceil(WORD_BITS);
+            CriteriaSet(CriteriaBits::Heap(vec![0; words].into_boxed_slice()))
+        }
     }
     pub fn _all(count: usize) -> Self {
-        assert!(
-            count <= MAX_CRITERIA,
-            "{MAX_CRITERIA} was not Enough For Everyone ({count} criteria)"
-        );
-        if count == MAX_CRITERIA {
-            CriteriaSet(!0)
-        } else {
-            // Bit Magic to get 'count' 1's
-            CriteriaSet((1 << count) - 1)
+        let mut result = Self::none(count);
+        for (word_idx, word) in result.words_mut(0).iter_mut().enumerate() {
+            // Bit Magic to get the right number of 1's in this word
+            let bits = count.saturating_sub(word_idx * WORD_BITS);
+            *word = if bits >= WORD_BITS {
+                !0
+            } else {
+                (1 << bits) - 1
+            };
         }
+        result
     }
     pub fn set_criteria(&mut self, idx: usize) {
-        self.0 |= 1 << idx;
+        let (word, bit) = (idx / WORD_BITS, idx % WORD_BITS);
+        self.words_mut(word + 1)[word] |= 1 << bit;
     }
     pub fn clear_criteria(&mut self, other: &CriteriaSet) {
-        self.0 &= !other.0;
+        if let (CriteriaBits::Inline(bits), CriteriaBits::Inline(other)) = (&mut self.0, &other.0) {
+            *bits &= !other;
+            return;
+        }
+        for (word, other) in self.words_mut(0).iter_mut().zip(other.words()) {
+            *word &= !other;
+        }
     }
     pub fn has_criteria(&self, idx: usize) -> bool {
-        (self.0 & (1 << idx)) != 0
+        let (word, bit) = (idx / WORD_BITS, idx % WORD_BITS);
+        self.words()
+            .get(word)
+            .is_some_and(|word| (word & (1 << bit)) != 0)
     }
-    pub fn _intersected_with(&mut self, other: &CriteriaSet) {
-        self.0 &= other.0;
+    pub fn intersected_with(&mut self, other: &CriteriaSet) {
+        if let (CriteriaBits::Inline(bits), CriteriaBits::Inline(other)) = (&mut self.0, &other.0) {
+            *bits &= other;
+            return;
+        }
+        let other = other.words();
+        for (idx, word) in self.words_mut(0).iter_mut().enumerate() {
+            *word &= other.get(idx).copied().unwrap_or(0);
+        }
     }
     pub fn unioned_with(&mut self, other: &CriteriaSet) {
-        self.0 |= other.0;
+        if let (CriteriaBits::Inline(bits), CriteriaBits::Inline(other)) = (&mut self.0, &other.0) {
+            *bits |= other;
+            return;
+        }
+        let other = other.words();
+        for (word, other) in self.words_mut(other.len()).iter_mut().zip(other) {
+            *word |= other;
+        }
     }
     pub fn contains(&self, other: &CriteriaSet) -> bool {
-        (self.0 & other.0) == other.0
+        if let (CriteriaBits::Inline(bits), CriteriaBits::Inline(other)) = (&self.0, &other.0) {
+            return (bits & other) == *other;
+        }
+        let words = self.words();
+        other.words().iter().enumerate().all(|(idx, other)| {
+            let word = words.get(idx).copied().unwrap_or(0);
+            (word & other) == *other
+        })
     }
     pub fn is_empty(&self) -> bool {
-        self.0 == 0
+        self.words().iter().all(|&word| word == 0)
     }
     pub fn indices(&self) -> impl Iterator<Item = usize> + '_ {
         // Yield all the offsets that are set by repeatedly getting the lowest 1 and clearing it
-        let mut raw = self.0;
-        std::iter::from_fn(move || {
-            if raw == 0 {
-                None
-            } else {
-                let next = raw.trailing_zeros() as usize;
-                raw &= !(1 << next);
-                Some(next)
+        self.words()
+            .iter()
+            .enumerate()
+            .flat_map(|(word_idx, &word)| {
+                let mut raw = word;
+                std::iter::from_fn(move || {
+                    if raw == 0 {
+                        None
+                    } else {
+                        let next = raw.trailing_zeros() as usize;
+                        raw &= !(1 << next);
+                        Some(word_idx * WORD_BITS + next)
+                    }
+                })
+            })
+    }
+
+    fn words(&self) -> &[u64] {
+        match &self.0 {
+            CriteriaBits::Inline(bits) => std::slice::from_ref(bits),
+            CriteriaBits::Heap(words) => words,
+        }
+    }
+    /// The words of this set, growing it to at least `len` words first.
+    fn words_mut(&mut self, len: usize) -> &mut [u64] {
+        match &mut self.0 {
+            CriteriaBits::Inline(bits) if len > 1 => {
+                let mut words = vec![0; len];
+                words[0] = *bits;
+                self.0 = CriteriaBits::Heap(words.into_boxed_slice());
             }
-        })
+            CriteriaBits::Heap(words) if len > words.len() => {
+                let mut grown = vec![0; len];
+                grown[..words.len()].copy_from_slice(words);
+                *words = grown.into_boxed_slice();
+            }
+            _ => {}
+        }
+        match &mut self.0 {
+            CriteriaBits::Inline(bits) => std::slice::from_mut(bits),
+            CriteriaBits::Heap(words) => words,
+        }
     }
 }
 
 impl fmt::Debug for CriteriaSet {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        write!(fmt, "{:08b}", self.0)
+        // Most significant word first, so it reads like one big binary number
+        let mut words = self.words().iter().rev();
+        if let Some(word) = words.next() {
+            write!(fmt, "{word:08b}")?;
+        }
+        for word in words {
+            write!(fmt, "_{word:064b}")?;
+        }
+        Ok(())
     }
 }
 
@@ -457,7 +804,14 @@ impl ResolveResult<'_> {
 }
 
 impl<'a> DepGraph<'a> {
-    pub fn new(metadata: &'a Metadata, filter_graph: Option<&Vec<GraphFilter>>) -> Self {
+    /// Build the graph for `metadata`. If `config` is provided, only the
+    /// dependencies that are active on the targets its policies ask for are
+    /// included, which fails if we can't find out about those targets.
+    pub fn new(
+        metadata: &'a Metadata,
+        filter_graph: Option<&Vec<GraphFilter>>,
+        config: Option<&ConfigFile>,
+    ) -> Result<Self, VetError> {
         let package_list = &*metadata.packages;
         let resolve_list = &*metadata
             .resolve
@@ -474,6 +828,10 @@ impl<'a> DepGraph<'a> {
             .enumerate()
             .map(|(idx, pkg)| (&pkg.id, idx))
             .collect();
+        let (targets, dev_targets) = match config {
+            Some(config) => compute_targets(metadata, &resolve_index_by_pkgid, config)?,
+            None => Default::default(),
+        };
 
         // Do a first-pass where we populate skeletons of the primary nodes
         // and setup the interners, which will only ever refer to these nodes
@@ -500,6 +858,7 @@ impl<'a> DepGraph<'a> {
                 is_workspace_member: false,
                 is_root: false,
                 is_dev_only: true,
+                targets: targets.get(&resolve_node.id).cloned().unwrap_or_default(),
             });
         }
 
@@ -540,6 +899,7 @@ impl<'a> DepGraph<'a> {
                     &interner_by_pkgid,
                     &resolve_index_by_pkgid,
                     resolve_list,
+                    &targets,
                     node_idx,
                 );
             }
@@ -560,10 +920,12 @@ impl<'a> DepGraph<'a> {
             for pkgid in &metadata.workspace_members {
                 let node_idx = interner_by_pkgid[pkgid];
                 let resolve_node = &resolve_list[resolve_index_by_pkgid[pkgid]];
+                let node_dev_targets = dev_targets.get(pkgid).unwrap_or(&ALL_TARGETS);
                 let dev_deps = deps(
                     resolve_node,
                     DependencyKind::Development,
                     &interner_by_pkgid,
+                    node_dev_targets,
                 );
 
                 // Now visit all the dev deps
@@ -575,6 +937,7 @@ impl<'a> DepGraph<'a> {
                         &interner_by_pkgid,
                         &resolve_index_by_pkgid,
                         resolve_list,
+                        &targets,
                         child,
                     );
                     // Note that these edges do not change whether something is a "root"
@@ -582,8 +945,15 @@ impl<'a> DepGraph<'a> {
                 }
 
                 let node = &mut nodes[node_idx];
+                node.all_deps = all_deps(
+                    resolve_node,
+                    &interner_by_pkgid,
+                    &node.targets,
+                    node_dev_targets,
+                );
                 node.dev_deps = dev_deps;
             }
+            #[allow(clippy::too_many_arguments)]
             fn visit_node<'a>(
                 nodes: &mut Vec<PackageNode<'a>>,
                 topo_index: &mut Vec<PackageIdx>,
@@ -591,6 +961,7 @@ impl<'a> DepGraph<'a> {
                 interner_by_pkgid: &SortedMap<&'a PackageId, PackageIdx>,
                 resolve_index_by_pkgid: &SortedMap<&'a PackageId, usize>,
                 resolve_list: &'a [cargo_metadata::Node],
+                targets: &FastMap<&'a PackageId, TargetSet>,
                 normal_idx: PackageIdx,
             ) {
                 // Don't revisit a node we've already seen
@@ -600,14 +971,23 @@ impl<'a> DepGraph<'a> {
                     let resolve_node =
                         &resolve_list[resolve_index_by_pkgid[nodes[normal_idx].package_id]];
 
-                    // Compute the different kinds of dependencies
-                    let all_deps = resolve_node
-                        .dependencies
-                        .iter()
-                        .map(|pkgid| interner_by_pkgid[pkgid])
-                        .collect::<Vec<_>>();
-                    let build_deps = deps(resolve_node, DependencyKind::Build, interner_by_pkgid);
-                    let normal_deps = deps(resolve_node, DependencyKind::Normal, interner_by_pkgid);
+                    // Compute the different kinds of dependencies, leaving out
+                    // the ones that aren't active on any target we're built for
+                    let node_targets = targets.get(&resolve_node.id).unwrap_or(&ALL_TARGETS);
+                    let all_deps =
+                        all_deps(resolve_node, interner_by_pkgid, node_targets, node_targets);
+                    let build_deps = deps(
+                        resolve_node,
+                        DependencyKind::Build,
+                        interner_by_pkgid,
+                        node_targets,
+                    );
+                    let normal_deps = deps(
+                        resolve_node,
+                        DependencyKind::Normal,
+                        interner_by_pkgid,
+                        node_targets,
+                    );
 
                     // Now visit all the build deps
                     for &child in &build_deps {
@@ -618,6 +998,7 @@ impl<'a> DepGraph<'a> {
                             interner_by_pkgid,
                             resolve_index_by_pkgid,
                             resolve_list,
+                            targets,
                             child,
                         );
                         nodes[child].reverse_deps.insert(normal_idx);
@@ -632,6 +1013,7 @@ impl<'a> DepGraph<'a> {
                             interner_by_pkgid,
                             resolve_index_by_pkgid,
                             resolve_list,
+                            targets,
                             child,
                         );
                         nodes[child].reverse_deps.insert(normal_idx);
@@ -653,32 +1035,61 @@ impl<'a> DepGraph<'a> {
                 resolve_node: &Node,
                 kind: DependencyKind,
                 interner_by_pkgid: &SortedMap<&PackageId, PackageIdx>,
+                targets: &TargetSet,
             ) -> Vec<PackageIdx> {
-                // Note that dep_kinds has target cfg info. If we want to handle targets
-                // we should gather those up with filter/fold instead of just 'any'.
                 // TODO: map normal-deps that whose package has a "proc-macro" target to be build-deps
                 resolve_node
                     .deps
                     .iter()
-                    .filter(|dep| dep.dep_kinds.iter().any(|dep_kind| dep_kind.kind == kind))
+                    .filter(|dep| {
+                        dep.dep_kinds.iter().any(|dep_kind| {
+                            dep_kind.kind == kind && targets.matches(dep_kind.target.as_ref())
+                        })
+                    })
+                    .map(|dep| interner_by_pkgid[&dep.pkg])
+                    .collect()
+            }
+            fn all_deps(
+                resolve_node: &Node,
+                interner_by_pkgid: &SortedMap<&PackageId, PackageIdx>,
+                targets: &TargetSet,
+                dev_targets: &TargetSet,
+            ) -> Vec<PackageIdx> {
+                resolve_node
+                    .deps
+                    .iter()
+                    .filter(|dep| {
+                        dep.dep_kinds.iter().any(|dep_kind| {
+                            let targets = if dep_kind.kind == DependencyKind::Development {
+                                dev_targets
+                            } else {
+                                targets
+                            };
+                            targets.matches(dep_kind.target.as_ref())
+                        })
+                    })
                     .map(|dep| interner_by_pkgid[&dep.pkg])
                     .collect()
             }
         }
 
-        let result = Self {
+        let mut result = Self {
             interner_by_pkgid,
             interner_by_name_and_ver,
             nodes,
             topo_index,
         };
 
+        // Drop the packages that only our targets' inactive dependencies needed
+        if result.topo_index.len() != result.nodes.len() {
+            result = result.filter(&[]);
+        }
+
         // Now apply filters, if any
         if let Some(filters) = filter_graph {
-            result.filter(filters)
-        } else {
-            result
+            result = result.filter(filters);
         }
+        Ok(result)
     }
 
     pub fn filter(self, filters: &[GraphFilter]) -> Self {
@@ -775,6 +1186,7 @@ impl<'a> DepGraph<'a> {
                 is_third_party: package.is_third_party,
                 is_root: package.is_root,
                 is_dev_only: package.is_dev_only,
+                targets: package.targets.clone(),
             });
             interner_by_pkgid.insert(package.package_id, new_idx);
             interner_by_name_and_ver
@@ -925,17 +1337,19 @@ impl<'a> DepGraph<'a> {
 // Dummy values for corner cases
 pub static ROOT_VERSION: Version = Version::new(0, 0, 0);
 static NO_AUDITS: Vec<AuditEntry> = Vec::new();
+static ALL_TARGETS: TargetSet = TargetSet::all();
+static NO_ACKNOWLEDGED_VIOLATIONS: Vec<AcknowledgedViolation> = Vec::new();
 
 pub fn resolve<'a>(
     metadata: &'a Metadata,
     filter_graph: Option<&Vec<GraphFilter>>,
     store: &'a Store,
     guess_deeper: bool,
-) -> ResolveReport<'a> {
+) -> Result<ResolveReport<'a>, VetError> {
     let _resolve_span = trace_span!("resolve").entered();
     // A large part of our algorithm is unioning and intersecting criteria, so we map all
     // the criteria into indexed boolean sets (*whispers* an integer with lots of bits).
-    let graph = DepGraph::new(metadata, filter_graph);
+    let graph = DepGraph::new(metadata, filter_graph, Some(&store.config))?;
     // trace!("built DepGraph: {:#?}", graph);
     trace!("built DepGraph!");
 
@@ -947,6 +1361,8 @@ pub fn resolve<'a>(
         vec![ResolveResult::with_no_criteria(criteria_mapper.no_criteria()); graph.nodes.len()];
     let mut root_failures = RootFailures::new();
     let mut violations = SortedMap::new();
+    let mut acknowledged_violations = SortedMap::new();
+    let filtered_foreign_audits = count_filtered_foreign_audits(store, &graph);
 
     // Actually vet the build graph
     for &pkgidx in &graph.topo_index {
@@ -960,6 +1376,7 @@ pub fn resolve<'a>(
                 &criteria_mapper,
                 &mut results,
                 &mut violations,
+                &mut acknowledged_violations,
                 &mut root_failures,
                 pkgidx,
             );
@@ -1027,49 +1444,31 @@ pub fn resolve<'a>(
 
     // If there were violations, report that
     if !violations.is_empty() {
-        return ResolveReport {
+        return Ok(ResolveReport {
             graph,
             criteria_mapper,
             results,
+            filtered_foreign_audits,
+            acknowledged_violations,
             conclusion: Conclusion::FailForViolationConflict(FailForViolationConflict {
                 violations,
             }),
-        };
+        });
     }
     _resolve_span.exit();
     let _blame_span = trace_span!("blame").entered();
 
     // There weren't any violations, so now compute the final failures by pushing blame
-    // down from the roots to the leaves that caused those failures.
-    let mut failures = SortedMap::<PackageIdx, AuditFailure>::new();
-    visit_failures(
+    // down from the roots to the leaves that caused those failures. Without any idea
+    // of how much work fixing each dependency is, blame the fewest we can.
+    let failures = blame_failures(
         &graph,
         &criteria_mapper,
         &results,
         &root_failures,
         guess_deeper,
-        |failure, depth, own_failure| {
-            if let Some(criteria_failures) = own_failure {
-                trace!(
-                    " {:width$}blaming: {}:{} for {:?}",
-                    "",
-                    graph.nodes[failure].name,
-                    graph.nodes[failure].version,
-                    criteria_mapper
-                        .criteria_names(criteria_failures)
-                        .collect::<Vec<_>>(),
-                    width = depth
-                );
-                failures
-                    .entry(failure)
-                    .or_default()
-                    .criteria_failures
-                    .unioned_with(criteria_failures);
-            }
-            Ok::<(), ()>(())
-        },
-    )
-    .unwrap();
+        |_| 0,
+    );
 
     // There should always be leaf failures if there were root failures!
     assert_eq!(
@@ -1080,15 +1479,19 @@ pub fn resolve<'a>(
 
     // If there are any failures, report that
     if !failures.is_empty() {
-        return ResolveReport {
+        return Ok(ResolveReport {
             graph,
             criteria_mapper,
             results,
+            filtered_foreign_audits,
+            acknowledged_violations,
             conclusion: Conclusion::FailForVet(FailForVet {
                 failures,
                 suggest: None,
+                root_failures,
+                guess_deeper,
             }),
-        };
+        });
     }
 
     // Ok, we've actually completely succeeded! Gather up stats on that success.
@@ -1117,17 +1520,19 @@ pub fn resolve<'a>(
         }
     }
 
-    ResolveReport {
+    Ok(ResolveReport {
         graph,
         criteria_mapper,
         results,
+        filtered_foreign_audits,
+        acknowledged_violations,
         conclusion: Conclusion::Success(Success {
             vetted_with_unaudited,
             vetted_partially,
             vetted_fully,
             useless_unaudited,
         }),
-    }
+    })
 }
 
 #[allow(clippy::too_many_arguments, clippy::ptr_arg)]
@@ -1138,6 +1543,7 @@ fn resolve_third_party<'a>(
     criteria_mapper: &CriteriaMapper,
     results: &mut [ResolveResult<'a>],
     violations: &mut SortedMap<PackageIdx, Vec<ViolationConflict>>,
+    acknowledged: &mut SortedMap<PackageIdx, Vec<AcknowledgedConflicts>>,
     _root_failures: &mut RootFailures,
     pkgidx: PackageIdx,
 ) {
@@ -1159,7 +1565,10 @@ fn resolve_third_party<'a>(
     let mut violation_nodes = Vec::new();
 
     // Collect up all the deltas, their criteria, and dependency_criteria
-    for entry in own_audits.iter() {
+    for entry in own_audits
+        .iter()
+        .filter(|entry| entry_applies_to_targets(&package.targets, entry))
+    {
         // For uniformity, model a Full Audit as `0.0.0 -> x.y.z`
         let (from_ver, to_ver, dependency_criteria) = match &entry.kind {
             AuditKind::Full {
@@ -1187,13 +1596,13 @@ fn resolve_third_party<'a>(
             version: to_ver,
             criteria: criteria.clone(),
             dependency_criteria: dependency_criteria.clone(),
-            is_unaudited_entry: false,
+            origin: DeltaOrigin::OwnAudit(entry),
         });
         backward_nodes.entry(to_ver).or_default().push(DeltaEdge {
             version: from_ver,
             criteria,
             dependency_criteria,
-            is_unaudited_entry: false,
+            origin: DeltaOrigin::OwnAudit(entry),
         });
     }
 
@@ -1201,24 +1610,19 @@ fn resolve_third_party<'a>(
     for (foreign_name, foreign_audits) in &store.imports.audits {
         // Prep CriteriaSet machinery for comparing requirements
         let foreign_criteria_mapper = CriteriaMapper::new(&foreign_audits.criteria);
-        let criteria_map = &store
+        let import = store
             .config
             .imports
             .get(foreign_name)
-            .expect("Foreign Import isn't in config file (imports.lock outdated?)")
-            .criteria_map;
-        let criteria_map: Vec<(CriteriaStr, CriteriaSet)> = criteria_map
-            .iter()
-            .map(|mapping| {
-                let set = foreign_criteria_mapper.criteria_from_list(&mapping.theirs);
-                (&*mapping.ours, set)
-            })
-            .collect();
+            .expect("Foreign Import isn't in config file (imports.lock outdated?)");
+        let criteria_map = import_criteria_map(import, &foreign_criteria_mapper);
 
         for entry in foreign_audits
             .audits
             .get(package.name)
             .unwrap_or(&NO_AUDITS)
+            .iter()
+            .filter(|entry| entry_applies_to_targets(&package.targets, entry))
         {
             // For uniformity, model a Full Audit as `0.0.0 -> x.y.z`
             let (from_ver, to_ver, dependency_criteria) = match &entry.kind {
@@ -1235,10 +1639,37 @@ fn resolve_third_party<'a>(
                     continue;
                 }
             };
-            // TODO: figure out a reasonable way to map foreign dependency_criteria
-            if !dependency_criteria.is_empty() {
-                // Just discard this entry for now
-                warn!("discarding foreign audit with dependency_criteria (TODO)");
+            // Only what we trust the import for is scoped, their violations
+            // always count
+            if !foreign_audit_in_scope(import, package.name, entry) {
+                continue;
+            }
+            // Map the dependency_criteria. If they ask something of a dependency
+            // that we can't express with our criteria, we have no way to check
+            // it, so this entry can't vouch for anything.
+            let mut local_dependency_criteria = FastMap::default();
+            let mut unmappable = None;
+            for (dep_name, foreign_dep_criteria) in dependency_criteria {
+                match map_foreign_requirement(
+                    criteria_mapper,
+                    &foreign_criteria_mapper,
+                    &criteria_map,
+                    &foreign_criteria_mapper.criteria_from_list(foreign_dep_criteria),
+                ) {
+                    Some(local_dep_criteria) => {
+                        local_dependency_criteria.insert(&**dep_name, local_dep_criteria);
+                    }
+                    None => {
+                        unmappable = Some(dep_name);
+                        break;
+                    }
+                }
+            }
+            if let Some(dep_name) = unmappable {
+                trace!(
+                    "  not using audit of {} from {foreign_name}: what it requires of {dep_name} can't be mapped to our criteria",
+                    package.name
+                );
                 continue;
             }
 
@@ -1246,61 +1677,62 @@ fn resolve_third_party<'a>(
             let mut local_criteria = criteria_mapper.no_criteria();
             let foreign_criteria = foreign_criteria_mapper.criteria_from_entry(entry);
             for (local_implied, foreign_required) in &criteria_map {
-                if foreign_criteria.contains(foreign_required) {
-                    criteria_mapper.set_criteria(&mut local_criteria, local_implied);
+                if !foreign_criteria.contains(foreign_required) {
+                    continue;
                 }
+                // Also pick up everything our criteria implies
+                local_criteria.unioned_with(&criteria_mapper.criteria_from_list([local_implied]));
+            }
+            if let Some(max_criteria) = &import.max_criteria {
+                local_criteria
+                    .intersected_with(&criteria_mapper.criteria_from_list([max_criteria]));
             }
 
             forward_nodes.entry(from_ver).or_default().push(DeltaEdge {
                 version: to_ver,
                 criteria: local_criteria.clone(),
-                dependency_criteria: Default::default(),
-                is_unaudited_entry: false,
+                dependency_criteria: local_dependency_criteria.clone(),
+                origin: DeltaOrigin::ForeignAudit(foreign_name, entry),
             });
             backward_nodes.entry(to_ver).or_default().push(DeltaEdge {
                 version: from_ver,
                 criteria: local_criteria,
-                dependency_criteria: Default::default(),
-                is_unaudited_entry: false,
+                dependency_criteria: local_dependency_criteria,
+                origin: DeltaOrigin::ForeignAudit(foreign_name, entry),
             });
         }
     }
 
     // Reject forbidden packages (violations)
-    //
-    // FIXME: should the "local" audit have a mechanism to override foreign forbids?
     for (violation_source, violation_entry) in &violation_nodes {
         let violation_range = if let AuditKind::Violation { violation } = &violation_entry.kind {
             violation
         } else {
             unreachable!("violation_entry wasn't a Violation?");
         };
+        let mut conflicts = Vec::new();
 
         // Note if this entry conflicts with any audits
         for audit in own_audits {
             match &audit.kind {
                 AuditKind::Full { version, .. } => {
                     if violation_range.matches(version) {
-                        violations.entry(pkgidx).or_default().push(
-                            ViolationConflict::AuditConflict {
-                                violation_source: violation_source.clone(),
-                                violation: (*violation_entry).clone(),
-                                audit_source: AuditSource::OwnAudits,
-                                audit: audit.clone(),
-                            },
-                        );
+                        conflicts.push(ViolationConflict::AuditConflict {
+                            violation_source: violation_source.clone(),
+                            violation: (*violation_entry).clone(),
+                            audit_source: AuditSource::OwnAudits,
+                            audit: audit.clone(),
+                        });
                     }
                 }
                 AuditKind::Delta { delta, .. } => {
                     if violation_range.matches(&delta.from) || violation_range.matches(&delta.to) {
-                        violations.entry(pkgidx).or_default().push(
-                            ViolationConflict::AuditConflict {
-                                violation_source: violation_source.clone(),
-                                violation: (*violation_entry).clone(),
-                                audit_source: AuditSource::OwnAudits,
-                                audit: audit.clone(),
-                            },
-                        );
+                        conflicts.push(ViolationConflict::AuditConflict {
+                            violation_source: violation_source.clone(),
+                            violation: (*violation_entry).clone(),
+                            audit_source: AuditSource::OwnAudits,
+                            audit: audit.clone(),
+                        });
                     }
                 }
                 AuditKind::Violation { .. } => {
@@ -1309,36 +1741,35 @@ fn resolve_third_party<'a>(
             }
         }
         for (foreign_name, foreign_audits) in &store.imports.audits {
+            let import = &store.config.imports[foreign_name];
             for audit in foreign_audits
                 .audits
                 .get(package.name)
                 .unwrap_or(&NO_AUDITS)
+                .iter()
+                .filter(|audit| foreign_audit_in_scope(import, package.name, audit))
             {
                 match &audit.kind {
                     AuditKind::Full { version, .. } => {
                         if violation_range.matches(version) {
-                            violations.entry(pkgidx).or_default().push(
-                                ViolationConflict::AuditConflict {
-                                    violation_source: violation_source.clone(),
-                                    violation: (*violation_entry).clone(),
-                                    audit_source: AuditSource::Foreign(foreign_name.clone()),
-                                    audit: audit.clone(),
-                                },
-                            );
+                            conflicts.push(ViolationConflict::AuditConflict {
+                                violation_source: violation_source.clone(),
+                                violation: (*violation_entry).clone(),
+                                audit_source: AuditSource::Foreign(foreign_name.clone()),
+                                audit: audit.clone(),
+                            });
                         }
                     }
                     AuditKind::Delta { delta, .. } => {
                         if violation_range.matches(&delta.from)
                             || violation_range.matches(&delta.to)
                         {
-                            violations.entry(pkgidx).or_default().push(
-                                ViolationConflict::AuditConflict {
-                                    violation_source: violation_source.clone(),
-                                    violation: (*violation_entry).clone(),
-                                    audit_source: AuditSource::Foreign(foreign_name.clone()),
-                                    audit: audit.clone(),
-                                },
-                            );
+                            conflicts.push(ViolationConflict::AuditConflict {
+                                violation_source: violation_source.clone(),
+                                violation: (*violation_entry).clone(),
+                                audit_source: AuditSource::Foreign(foreign_name.clone()),
+                                audit: audit.clone(),
+                            });
                         }
                     }
                     AuditKind::Violation { .. } => {
@@ -1350,13 +1781,36 @@ fn resolve_third_party<'a>(
 
         // Note if this entry conflicts with the current package's version
         if violation_range.matches(package.version) {
-            violations
+            conflicts.push(ViolationConflict::CurVersionConflict {
+                source: violation_source.clone(),
+                violation: (*violation_entry).clone(),
+            });
+        }
+
+        if conflicts.is_empty() {
+            continue;
+        }
+        // A foreign violation can be acknowledged in our config, in which case
+        // we only warn about it instead of failing.
+        let acknowledgement = match violation_source {
+            AuditSource::Foreign(foreign_name) => store
+                .config
+                .acknowledged_violations
+                .get(package.name)
+                .unwrap_or(&NO_ACKNOWLEDGED_VIOLATIONS)
+                .iter()
+                .find(|ack| &ack.import == foreign_name && &ack.violation == violation_range),
+            AuditSource::OwnAudits => None,
+        };
+        match acknowledgement {
+            Some(ack) => acknowledged
                 .entry(pkgidx)
                 .or_default()
-                .push(ViolationConflict::CurVersionConflict {
-                    source: violation_source.clone(),
-                    violation: (*violation_entry).clone(),
-                });
+                .push(AcknowledgedConflicts {
+                    conflicts,
+                    justification: ack.justification.clone(),
+                }),
+            None => violations.entry(pkgidx).or_default().extend(conflicts),
         }
     }
 
@@ -1387,13 +1841,13 @@ fn resolve_third_party<'a>(
                 version: to_ver,
                 criteria: criteria.clone(),
                 dependency_criteria: dependency_criteria.clone(),
-                is_unaudited_entry: true,
+                origin: DeltaOrigin::Unaudited(allowed),
             });
             backward_nodes.entry(to_ver).or_default().push(DeltaEdge {
                 version: from_ver,
                 criteria,
                 dependency_criteria,
-                is_unaudited_entry: true,
+                origin: DeltaOrigin::Unaudited(allowed),
             });
         }
     }
@@ -1413,18 +1867,24 @@ fn resolve_third_party<'a>(
             results,
         );
         match result {
-            SearchResult::Connected { fully_audited } => {
+            SearchResult::Connected {
+                fully_audited,
+                path,
+            } => {
                 // We found a path, hooray, criteria validated!
                 if fully_audited {
                     fully_audited_criteria.unioned_with(criteria);
                 }
                 validated_criteria.unioned_with(criteria);
-                search_results.push(SearchResult::Connected { fully_audited });
+                search_results.push(SearchResult::Connected {
+                    fully_audited,
+                    path,
+                });
             }
-            SearchResult::PossiblyConnected { failed_deps } => {
+            SearchResult::PossiblyConnected { alternatives } => {
                 // We failed but found a possible solution if our dependencies were better.
                 // Just forward this along so that we can blame them if it comes up!
-                search_results.push(SearchResult::PossiblyConnected { failed_deps });
+                search_results.push(SearchResult::PossiblyConnected { alternatives });
             }
             SearchResult::Disconnected {
                 reachable_from_root,
@@ -1504,46 +1964,52 @@ fn search_for_path<'a>(
     let mut found_path = false;
     let mut needed_unaudited_entry = false;
     let mut needed_failed_edges = false;
-    let mut failed_deps = SortedMap::<PackageIdx, CriteriaSet>::new();
 
     // Search State
-    let mut search_stack = vec![from_version];
+    let mut search_stack = vec![(from_version, None)];
     let mut visited = SortedSet::new();
-    let mut deferred_unaudited_entries = vec![];
-    let mut deferred_failed_edges = vec![];
+    // The edge we first reached each version through, so we can report the path.
+    let mut parents = SortedMap::<&'a Version, AuditStep<'a>>::new();
+    let mut deferred_unaudited_entries = Vec::<AuditStep<'a>>::new();
+    let mut deferred_failed_edges = Vec::<AuditStep<'a>>::new();
 
     // Loop until we find a path or run out of deferred edges.
     loop {
         // If there are any deferred edges (only possible on iteration 2+), try to follow them.
         // Always prefer following 'unaudited' edges, so that we only dip into failed edges when
         // we've completely run out of options.
-        if let Some(node) = deferred_unaudited_entries.pop() {
+        if let Some(step) = deferred_unaudited_entries.pop() {
             // Don't bother if we got to that node some other way.
-            if visited.contains(node) {
+            if visited.contains(step.to) {
                 continue;
             }
             // Ok at this point we officially "need" the unaudited edge. If the search still
             // fails, then we won't mention that we used this, since the graph is just broken
             // and we can't make any conclusions about whether anything is needed or not!
             needed_unaudited_entry = true;
-            search_stack.push(node);
-        } else if let Some(node) = deferred_failed_edges.pop() {
+            search_stack.push((step.to, Some(step)));
+        } else if let Some(step) = deferred_failed_edges.pop() {
             // Don't bother if we got to that node some other way.
-            if visited.contains(node) {
+            if visited.contains(step.to) {
                 continue;
             }
             // Ok at this point we officially "need" the failed edge. If the search still
             // fails, then we won't mention that we used this, since the graph is just broken
             // and we can't make any conclusions about whether anything is needed or not!
             needed_failed_edges = true;
-            search_stack.push(node);
+            search_stack.push((step.to, Some(step)));
         }
 
         // Do Depth-First-Search
-        while let Some(cur_version) = search_stack.pop() {
+        while let Some((cur_version, step)) = search_stack.pop() {
             // Don't revisit nodes, there's never an advantage to doing so, and because deltas
             // can go both forwards and backwards in time, cycles are a real concern!
-            visited.insert(cur_version);
+            if !visited.insert(cur_version) {
+                continue;
+            }
+            if let Some(step) = step {
+                parents.insert(cur_version, step);
+            }
             if cur_version == to_version {
                 // Success! Nothing more to do.
                 found_path = true;
@@ -1563,39 +2029,26 @@ fn search_for_path<'a>(
                     }
 
                     // Deltas should only apply if dependencies satisfy dep_criteria
-                    let mut deps_satisfied = true;
-                    for &dependency in &package.all_deps {
-                        let dep_package = &dep_graph.nodes[dependency];
-                        let dep_vet_result = &mut results[dependency];
-
-                        // If no custom criteria is specified, then require our dependency to match
-                        // the same criteria that this delta claims to provide.
-                        // e.g. a 'secure' audit requires all dependencies to be 'secure' by default.
-                        let dep_req = edge
-                            .dependency_criteria
-                            .get(&*dep_package.name)
-                            .unwrap_or(&edge.criteria);
-
-                        if !dep_vet_result.contains(dep_req) {
-                            failed_deps
-                                .entry(dependency)
-                                .or_insert_with(|| criteria_mapper.no_criteria())
-                                .unioned_with(dep_req);
-                            deps_satisfied = false;
-                        }
-                    }
-
+                    let deps_satisfied =
+                        edge_failed_deps(edge, dep_graph, criteria_mapper, package, results)
+                            .is_empty();
+
+                    let step = AuditStep {
+                        from: cur_version,
+                        to: edge.version,
+                        origin: edge.origin,
+                    };
                     if deps_satisfied {
                         // Ok yep, this edge is usable! But defer it if it's an 'unaudited' entry.
-                        if edge.is_unaudited_entry {
-                            deferred_unaudited_entries.push(edge.version);
+                        if let DeltaOrigin::Unaudited(_) = edge.origin {
+                            deferred_unaudited_entries.push(step);
                         } else {
-                            search_stack.push(edge.version);
+                            search_stack.push((edge.version, Some(step)));
                         }
                     } else {
                         // Remember this edge failed, if we can't find any path we'll speculatively
                         // re-enable it.
-                        deferred_failed_edges.push(edge.version);
+                        deferred_failed_edges.push(step);
                     }
                 }
             }
@@ -1610,13 +2063,33 @@ fn search_for_path<'a>(
 
     // It's only a success if we found a path and used no 'failed' edges.
     if found_path && !needed_failed_edges {
-        // Complete success!
+        // Complete success! Walk back from the target to recover the path we took.
+        let mut path = vec![];
+        let mut cur_version = to_version;
+        while let Some(step) = parents.remove(cur_version) {
+            cur_version = step.from;
+            path.push(step);
+        }
+        path.reverse();
         SearchResult::Connected {
             fully_audited: !needed_unaudited_entry,
+            path,
         }
     } else if found_path {
-        // Failure, but it's clearly the fault of our deps.
-        SearchResult::PossiblyConnected { failed_deps }
+        // Failure, but it's clearly the fault of our deps. Work out exactly which
+        // combinations of them are to blame.
+        SearchResult::PossiblyConnected {
+            alternatives: minimal_failed_deps(
+                cur_criteria,
+                from_version,
+                to_version,
+                version_nodes,
+                dep_graph,
+                criteria_mapper,
+                package,
+                results,
+            ),
+        }
     } else {
         // Complete failure, we need more audits of ourself,
         // so all that matters is what nodes were reachable.
@@ -1628,65 +2101,306 @@ fn search_for_path<'a>(
     }
 }
 
-#[allow(clippy::too_many_arguments, clippy::ptr_arg)]
-fn resolve_first_party<'a>(
-    _metadata: &'a Metadata,
-    store: &'a Store,
-    graph: &DepGraph<'a>,
+/// The dependencies of `package` that don't satisfy what `edge` requires of them.
+fn edge_failed_deps(
+    edge: &DeltaEdge,
+    dep_graph: &DepGraph,
     criteria_mapper: &CriteriaMapper,
-    results: &mut [ResolveResult<'a>],
-    _violations: &mut SortedMap<PackageIdx, Vec<ViolationConflict>>,
-    root_failures: &mut RootFailures,
-    pkgidx: PackageIdx,
-) {
-    // Check the build-deps and normal-deps of this package. dev-deps are checking in `resolve_dep`
-    // In this pass we properly use package.is_root, but in the next pass all nodes are "roots"
-    let package = &graph.nodes[pkgidx];
-
-    // Get custom policies for our dependencies
-    let dep_criteria = store
-        .config
-        .policy
-        .get(package.name)
-        .map(|policy| {
-            policy
-                .dependency_criteria
-                .iter()
-                .map(|(dep_name, criteria)| {
-                    (&**dep_name, criteria_mapper.criteria_from_list(criteria))
-                })
-                .collect::<FastMap<_, _>>()
-        })
-        .unwrap_or_default();
-
-    // Compute whether we have each criteria based on our dependencies
-    let mut validated_criteria = criteria_mapper.no_criteria();
-    let mut search_results = vec![];
-    for criteria in criteria_mapper.all_criteria_iter() {
-        // Find any build/normal dependencies that don't satisfy this criteria
-        let mut failed_deps = SortedMap::new();
-        for &depidx in package.normal_deps.iter().chain(&package.build_deps) {
-            // If we have an explicit policy for dependency, that's all that matters.
-            // Otherwise just use the current criteria to "inherit" the results of our deps.
-            let dep_name = graph.nodes[depidx].name;
-            let required_criteria = dep_criteria.get(dep_name).unwrap_or(criteria);
-            if !results[depidx].contains(required_criteria) {
-                failed_deps
-                    .entry(depidx)
-                    .or_insert_with(|| criteria_mapper.no_criteria())
-                    .unioned_with(required_criteria);
-            }
+    package: &PackageNode,
+    results: &mut [ResolveResult],
+) -> FailedDeps {
+    let mut failed_deps = FailedDeps::new();
+    for &dependency in &package.all_deps {
+        let dep_package = &dep_graph.nodes[dependency];
+
+        // If no custom criteria is specified, then require our dependency to match
+        // the same criteria that this delta claims to provide.
+        // e.g. a 'secure' audit requires all dependencies to be 'secure' by default.
+        let dep_req = edge
+            .dependency_criteria
+            .get(dep_package.name)
+            .unwrap_or(&edge.criteria);
+
+        if !results[dependency].contains(dep_req) {
+            failed_deps
+                .entry(dependency)
+                .or_insert_with(|| criteria_mapper.no_criteria())
+                .unioned_with(dep_req);
+        }
+    }
+    failed_deps
+}
+
+/// Find the minimal sets of failed dependencies that block every path from
+/// `from_version` to `to_version`, so that fixing any one set (and nothing more)
+/// would connect them.
+///
+/// Every version gets the list of minimal sets of failed dependencies we've
+/// reached it with so far, and we keep following edges until no list changes.
+/// The number of sets kept per version is capped at `MAX_FAILED_DEPS_ALTERNATIVES`,
+/// so with many independent ways to fail only the smallest ones are found.
+#[allow(clippy::too_many_arguments)]
+fn minimal_failed_deps<'a>(
+    cur_criteria: &CriteriaSet,
+    from_version: &'a Version,
+    to_version: &'a Version,
+    version_nodes: &SortedMap<&'a Version, Vec<DeltaEdge<'a>>>,
+    dep_graph: &DepGraph<'a>,
+    criteria_mapper: &CriteriaMapper,
+    package: &PackageNode<'a>,
+    results: &mut [ResolveResult],
+) -> Vec<FailedDeps> {
+    let mut reached_with = SortedMap::<&'a Version, Vec<FailedDeps>>::new();
+    reached_with.insert(from_version, vec![FailedDeps::new()]);
+    let mut worklist = vec![from_version];
+    while let Some(cur_version) = worklist.pop() {
+        let edges = match version_nodes.get(cur_version) {
+            Some(edges) => edges,
+            None => continue,
+        };
+        for edge in edges {
+            if !edge.criteria.contains(cur_criteria) {
+                continue;
+            }
+            let edge_failures =
+                edge_failed_deps(edge, dep_graph, criteria_mapper, package, results);
+            let mut grew = false;
+            for failed_deps in reached_with[cur_version].clone() {
+                let mut candidate = failed_deps;
+                for (&dependency, criteria) in &edge_failures {
+                    candidate
+                        .entry(dependency)
+                        .or_insert_with(|| criteria_mapper.no_criteria())
+                        .unioned_with(criteria);
+                }
+                let existing = reached_with.entry(edge.version).or_default();
+                if existing
+                    .iter()
+                    .any(|other| failed_deps_subset(other, &candidate))
+                {
+                    continue;
+                }
+                existing.retain(|other| !failed_deps_subset(&candidate, other));
+                if existing.len() >= MAX_FAILED_DEPS_ALTERNATIVES {
+                    // Keep the smallest sets so the worklist can't blow up, dropping
+                    // the candidate if it's no smaller than any of them.
+                    let largest = (0..existing.len())
+                        .max_by_key(|&idx| existing[idx].len())
+                        .unwrap();
+                    if existing[largest].len() <= candidate.len() {
+                        continue;
+                    }
+                    existing.swap_remove(largest);
+                }
+                existing.push(candidate);
+                grew = true;
+            }
+            if grew {
+                worklist.push(edge.version);
+            }
+        }
+    }
+
+    let mut alternatives = reached_with.remove(to_version).unwrap_or_default();
+    alternatives.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.keys().cmp(b.keys())));
+    alternatives
+}
+
+/// The most sets of failed dependencies `minimal_failed_deps` keeps per version.
+/// This runs for every criteria that our dependencies are to blame for, so the
+/// number of combinations has to stay small.
+pub const MAX_FAILED_DEPS_ALTERNATIVES: usize = 8;
+
+/// Whether fixing the dependencies in `a` is part of fixing those in `b`.
+fn failed_deps_subset(a: &FailedDeps, b: &FailedDeps) -> bool {
+    a.iter().all(|(dependency, criteria)| {
+        b.get(dependency)
+            .is_some_and(|other| other.contains(criteria))
+    })
+}
+
+/// Whether `entry`, an audit of `package` from `import`, is within the scope
+/// that we trust the import for.
+fn foreign_audit_in_scope(import: &RemoteImport, package: PackageStr, entry: &AuditEntry) -> bool {
+    if !import.include.is_empty() && !import.include.iter().any(|name| name == package) {
+        return false;
+    }
+    if import.exclude.iter().any(|name| name == package) {
+        return false;
+    }
+    if !import.auditors.is_empty() {
+        let trusted = entry
+            .who
+            .as_ref()
+            .is_some_and(|who| import.auditors.contains(who));
+        if !trusted {
+            return false;
+        }
+    }
+    true
+}
+
+/// Whether `entry` applies to the targets a package is built for. An audit has to cover
+/// all of them to be trusted, but a violation applies if it concerns any of them.
+///
+/// The cfgs of `targets` were looked up when building the graph, so this can't fail.
+fn entry_applies_to_targets(targets: &TargetSet, entry: &AuditEntry) -> bool {
+    match entry.kind {
+        AuditKind::Violation { .. } => targets.touched_by(entry.targets.as_deref()),
+        AuditKind::Full { .. } | AuditKind::Delta { .. } => {
+            targets.covered_by(entry.targets.as_deref())
+        }
+    }
+}
+
+/// Count the imported audits of the third-party packages in `graph` that are
+/// outside the scope of their import (violations are never out of scope).
+fn count_filtered_foreign_audits(store: &Store, graph: &DepGraph) -> usize {
+    let packages = graph
+        .nodes
+        .iter()
+        .filter(|package| package.is_third_party)
+        .map(|package| package.name)
+        .collect::<SortedSet<_>>();
+    let mut count = 0;
+    for (import_name, foreign_audits) in &store.imports.audits {
+        let import = match store.config.imports.get(import_name) {
+            Some(import) => import,
+            None => continue,
+        };
+        for package in &packages {
+            count += foreign_audits
+                .audits
+                .get(*package)
+                .unwrap_or(&NO_AUDITS)
+                .iter()
+                .filter(|entry| {
+                    !matches!(entry.kind, AuditKind::Violation { .. })
+                        && !foreign_audit_in_scope(import, package, entry)
+                })
+                .count();
+        }
+    }
+    count
+}
+
+/// The criteria-map of `import`, as pairs of our criteria and the set of their
+/// criteria that implies it, including the built-in criteria if it maps those.
+fn import_criteria_map<'a>(
+    import: &'a RemoteImport,
+    foreign_criteria_mapper: &CriteriaMapper,
+) -> Vec<(CriteriaStr<'a>, CriteriaSet)> {
+    let mut criteria_map: Vec<(CriteriaStr, CriteriaSet)> = import
+        .criteria_map
+        .iter()
+        .map(|mapping| {
+            let set = foreign_criteria_mapper.criteria_from_list(&mapping.theirs);
+            (&*mapping.ours, set)
+        })
+        .collect();
+    if import.map_builtins {
+        // The built-in criteria mean the same thing everywhere
+        for builtin in [SAFE_TO_DEPLOY, SAFE_TO_RUN] {
+            let set = foreign_criteria_mapper.criteria_from_list([builtin]);
+            criteria_map.push((builtin, set));
+        }
+    }
+    criteria_map
+}
+
+/// Translate something an import requires of a dependency (`foreign_criteria`)
+/// into our criteria, through the import's `criteria_map`.
+///
+/// Each of the strongest criteria they require has to have a mapping of its
+/// own: going through a mapping of something weaker that it implies would ask
+/// less of the dependency than they did. If any of them doesn't, we have no
+/// way to check the requirement, and this returns `None`.
+fn map_foreign_requirement(
+    criteria_mapper: &CriteriaMapper,
+    foreign_criteria_mapper: &CriteriaMapper,
+    criteria_map: &[(CriteriaStr, CriteriaSet)],
+    foreign_criteria: &CriteriaSet,
+) -> Option<CriteriaSet> {
+    let mut local_criteria = criteria_mapper.no_criteria();
+    for name in foreign_criteria_mapper.criteria_names(foreign_criteria) {
+        let theirs = &foreign_criteria_mapper.implied_criteria[foreign_criteria_mapper.index[name]];
+        let mut mapped = false;
+        for (ours, foreign_required) in criteria_map {
+            if foreign_required.contains(theirs) && theirs.contains(foreign_required) {
+                local_criteria.unioned_with(&criteria_mapper.criteria_from_list([*ours]));
+                mapped = true;
+            }
+        }
+        if !mapped {
+            return None;
+        }
+    }
+    Some(local_criteria)
+}
+
+#[allow(clippy::too_many_arguments, clippy::ptr_arg)]
+fn resolve_first_party<'a>(
+    _metadata: &'a Metadata,
+    store: &'a Store,
+    graph: &DepGraph<'a>,
+    criteria_mapper: &CriteriaMapper,
+    results: &mut [ResolveResult<'a>],
+    _violations: &mut SortedMap<PackageIdx, Vec<ViolationConflict>>,
+    root_failures: &mut RootFailures,
+    pkgidx: PackageIdx,
+) {
+    // Check the build-deps and normal-deps of this package. dev-deps are checking in `resolve_dep`
+    // In this pass we properly use package.is_root, but in the next pass all nodes are "roots"
+    let package = &graph.nodes[pkgidx];
+
+    // Get custom policies for our dependencies
+    let dep_criteria = store
+        .config
+        .policy
+        .get(package.name)
+        .map(|policy| {
+            policy
+                .dependency_criteria
+                .iter()
+                .map(|(dep_name, criteria)| {
+                    (&**dep_name, criteria_mapper.criteria_from_list(criteria))
+                })
+                .collect::<FastMap<_, _>>()
+        })
+        .unwrap_or_default();
+
+    // Compute whether we have each criteria based on our dependencies
+    let mut validated_criteria = criteria_mapper.no_criteria();
+    let mut search_results = vec![];
+    for criteria in criteria_mapper.all_criteria_iter() {
+        // Find any build/normal dependencies that don't satisfy this criteria
+        let mut failed_deps = SortedMap::new();
+        for &depidx in package.normal_deps.iter().chain(&package.build_deps) {
+            // If we have an explicit policy for dependency, that's all that matters.
+            // Otherwise just use the current criteria to "inherit" the results of our deps.
+            let dep_name = graph.nodes[depidx].name;
+            let required_criteria = dep_criteria.get(dep_name).unwrap_or(criteria);
+            if !results[depidx].contains(required_criteria) {
+                failed_deps
+                    .entry(depidx)
+                    .or_insert_with(|| criteria_mapper.no_criteria())
+                    .unioned_with(required_criteria);
+            }
         }
 
         if failed_deps.is_empty() {
             // All our deps passed the test, so we have this criteria
             search_results.push(SearchResult::Connected {
                 fully_audited: true,
+                path: vec![],
             });
             validated_criteria.unioned_with(criteria);
         } else {
-            // Some of our deps failed to satisfy this criteria, record this
-            search_results.push(SearchResult::PossiblyConnected { failed_deps })
+            // Some of our deps failed to satisfy this criteria, record this. We need all
+            // our deps, so there's only the one alternative.
+            search_results.push(SearchResult::PossiblyConnected {
+                alternatives: vec![failed_deps],
+            })
         }
     }
     trace!(
@@ -1715,10 +2429,10 @@ fn resolve_first_party<'a>(
 
     let mut policy_failures = PolicyFailures::new();
     for criteria_idx in own_policy.indices() {
-        if let SearchResult::PossiblyConnected { failed_deps } =
+        if let SearchResult::PossiblyConnected { alternatives } =
             &results[pkgidx].search_results[criteria_idx]
         {
-            for (&dep, failed_criteria) in failed_deps {
+            for (&dep, failed_criteria) in alternatives.iter().flatten() {
                 policy_failures
                     .entry(dep)
                     .or_insert_with(|| criteria_mapper.no_criteria())
@@ -1795,11 +2509,15 @@ fn resolve_dev<'a>(
             // All our deps passed the test, so we have this criteria
             search_results.push(SearchResult::Connected {
                 fully_audited: true,
+                path: vec![],
             });
             validated_criteria.unioned_with(criteria);
         } else {
-            // Some of our deps failed to satisfy this criteria, record this
-            search_results.push(SearchResult::PossiblyConnected { failed_deps })
+            // Some of our deps failed to satisfy this criteria, record this. We need all
+            // our deps, so there's only the one alternative.
+            search_results.push(SearchResult::PossiblyConnected {
+                alternatives: vec![failed_deps],
+            })
         }
     }
     trace!(
@@ -1825,8 +2543,8 @@ fn resolve_dev<'a>(
 
     let mut policy_failures = PolicyFailures::new();
     for criteria_idx in own_policy.indices() {
-        if let SearchResult::PossiblyConnected { failed_deps } = &search_results[criteria_idx] {
-            for (&dep, failed_criteria) in failed_deps {
+        if let SearchResult::PossiblyConnected { alternatives } = &search_results[criteria_idx] {
+            for (&dep, failed_criteria) in alternatives.iter().flatten() {
                 policy_failures
                     .entry(dep)
                     .or_insert_with(|| criteria_mapper.no_criteria())
@@ -1843,6 +2561,51 @@ fn resolve_dev<'a>(
     }
 }
 
+/// Push blame down from the root failures to the packages that need more audits.
+///
+/// When a package could be fixed by fixing any one of several sets of its
+/// dependencies, `choose_alternative` picks which set to blame.
+fn blame_failures<'a>(
+    graph: &DepGraph<'a>,
+    criteria_mapper: &CriteriaMapper,
+    results: &[ResolveResult<'a>],
+    root_failures: &RootFailures,
+    guess_deeper: bool,
+    choose_alternative: impl FnMut(&[FailedDeps]) -> usize,
+) -> SortedMap<PackageIdx, AuditFailure> {
+    let mut failures = SortedMap::<PackageIdx, AuditFailure>::new();
+    visit_failures(
+        graph,
+        criteria_mapper,
+        results,
+        root_failures,
+        guess_deeper,
+        choose_alternative,
+        |failure, depth, own_failure| {
+            if let Some(criteria_failures) = own_failure {
+                trace!(
+                    " {:width$}blaming: {}:{} for {:?}",
+                    "",
+                    graph.nodes[failure].name,
+                    graph.nodes[failure].version,
+                    criteria_mapper
+                        .criteria_names(criteria_failures)
+                        .collect::<Vec<_>>(),
+                    width = depth
+                );
+                failures
+                    .entry(failure)
+                    .or_default()
+                    .criteria_failures
+                    .unioned_with(criteria_failures);
+            }
+            Ok::<(), ()>(())
+        },
+    )
+    .unwrap();
+    failures
+}
+
 /// Traverse the build graph from the root failures to the leaf failures.
 fn visit_failures<'a, T>(
     graph: &DepGraph<'a>,
@@ -1850,6 +2613,7 @@ fn visit_failures<'a, T>(
     results: &[ResolveResult<'a>],
     root_failures: &RootFailures,
     guess_deeper: bool,
+    mut choose_alternative: impl FnMut(&[FailedDeps]) -> usize,
     mut callback: impl FnMut(PackageIdx, usize, Option<&CriteriaSet>) -> Result<(), T>,
 ) -> Result<(), T> {
     trace!(" traversing blame tree");
@@ -1956,9 +2720,15 @@ fn visit_failures<'a, T>(
                     SearchResult::Connected { .. } => {
                         // Do nothing, this package is good
                     }
-                    SearchResult::PossiblyConnected { failed_deps } => {
-                        // We're not to blame, it's our children who failed!
-                        for (&failed_dep, failed_criteria) in failed_deps {
+                    SearchResult::PossiblyConnected { alternatives } => {
+                        // We're not to blame, it's our children who failed! Fixing any
+                        // one set of them would do, so only blame that one.
+                        let chosen = if alternatives.len() > 1 {
+                            choose_alternative(alternatives)
+                        } else {
+                            0
+                        };
+                        for (&failed_dep, failed_criteria) in &alternatives[chosen] {
                             dep_faults
                                 .entry(failed_dep)
                                 .or_insert_with(|| no_criteria.clone())
@@ -2047,9 +2817,26 @@ impl<'a> ResolveReport<'a> {
         };
 
         let mut cache = Cache::acquire(cfg)?;
+
+        // Blame the failures again, now that we can look at the diffstats to find
+        // the cheapest set of dependencies to fix wherever there's a choice.
+        let mut fix_costs = FastMap::new();
+        let failures = blame_failures(
+            &self.graph,
+            &self.criteria_mapper,
+            &self.results,
+            &fail.root_failures,
+            fail.guess_deeper,
+            |alternatives| {
+                self.cheapest_alternative(&mut cache, allow_deltas, &mut fix_costs, alternatives)
+                    .0
+            },
+        );
+
         let mut suggestions = vec![];
+        let mut unsuggested = vec![];
         let mut total_lines: u64 = 0;
-        for (&failure_idx, audit_failure) in &fail.failures {
+        for (&failure_idx, audit_failure) in &failures {
             let package = &self.graph.nodes[failure_idx];
             let result = &self.results[failure_idx];
 
@@ -2094,11 +2881,11 @@ impl<'a> ResolveReport<'a> {
                     if let (Some(from_root), Some(from_target)) =
                         (from_root.as_mut(), from_target.as_mut())
                     {
-                        // FIXME: this is horrible but I'm tired and this avoids false-positives
-                        // and duplicates. This does the right thing in the common cases, by
-                        // restricting ourselves to the reachable nodes that are common to all
+                        // Restrict ourselves to the reachable nodes that are common to all
                         // failures, so that we can suggest just one change that will fix
-                        // everything.
+                        // everything. If the criteria go through different versions, that
+                        // might leave nothing but a full audit, so below we also consider
+                        // separate audits for each criteria.
                         *from_root = &*from_root & reachable_from_root;
                         *from_target = &*from_target & reachable_from_target;
                     } else {
@@ -2111,62 +2898,88 @@ impl<'a> ResolveReport<'a> {
             }
 
             // Now suggest solutions of those failures
-            let mut candidates = SortedSet::new();
-            if allow_deltas {
-                // If we're allowed deltas than try to find a bridge from src and dest
-                for &dest in from_target.as_ref().unwrap() {
-                    let mut closest_above = None;
-                    let mut closest_below = None;
-                    for &src in from_root.as_ref().unwrap() {
-                        if src < dest {
-                            if let Some(closest) = closest_below {
-                                if src > closest {
-                                    closest_below = Some(src);
-                                }
-                            } else {
-                                closest_below = Some(src);
-                            }
-                        } else if let Some(closest) = closest_above {
-                            if src < closest {
-                                closest_above = Some(src);
-                            }
-                        } else {
-                            closest_above = Some(src);
-                        }
-                    }
+            let candidates = suggest_candidates(
+                allow_deltas,
+                from_root.as_ref().unwrap(),
+                from_target.as_ref().unwrap(),
+            );
+            let combined = match cache.fetch_and_diffstat_all(package.name, &candidates) {
+                Ok(suggested_diff) => Some(suggested_diff),
+                Err(err) => {
+                    // We don't want to actually error out completely here since other packages
+                    // might still successfully diff!
+                    error!("error diffing {}:{} {}", package.name, package.version, err);
+                    None
+                }
+            };
 
-                    for closest in closest_below.into_iter().chain(closest_above) {
-                        candidates.insert(Delta {
-                            from: closest.clone(),
-                            to: dest.clone(),
-                        });
+            // If we failed several criteria (that don't imply each other), see whether
+            // an audit for each would be cheaper than one audit for all of them. That's
+            // only a plan if every one of them gets an audit.
+            let mut separate = vec![];
+            let strongest_criteria = self
+                .criteria_mapper
+                .criteria_names(&audit_failure.criteria_failures)
+                .map(|name| self.criteria_mapper.index[name])
+                .collect::<Vec<_>>();
+            if strongest_criteria.len() > 1 {
+                for &criteria_idx in &strongest_criteria {
+                    let (reachable_from_root, reachable_from_target) =
+                        match &result.search_results[criteria_idx] {
+                            SearchResult::Disconnected {
+                                reachable_from_root,
+                                reachable_from_target,
+                            } => (reachable_from_root, reachable_from_target),
+                            _ => break,
+                        };
+                    let candidates = suggest_candidates(
+                        allow_deltas,
+                        reachable_from_root,
+                        reachable_from_target,
+                    );
+                    match cache.fetch_and_diffstat_all(package.name, &candidates) {
+                        Ok(suggested_diff) => separate.push((
+                            self.criteria_mapper.implied_criteria[criteria_idx].clone(),
+                            suggested_diff,
+                        )),
+                        Err(err) => {
+                            error!("error diffing {}:{} {}", package.name, package.version, err);
+                            break;
+                        }
                     }
                 }
-            } else {
-                // If we're not allowing deltas, just try everything reachable from the target
-                for &dest in from_target.as_ref().unwrap() {
-                    candidates.insert(Delta {
-                        from: ROOT_VERSION.clone(),
-                        to: dest.clone(),
-                    });
+                if separate.len() < strongest_criteria.len() {
+                    separate.clear();
                 }
             }
+            let separate_lines = separate
+                .iter()
+                .map(|(_, suggested_diff)| suggested_diff.diffstat.count)
+                .sum::<u64>();
 
-            match cache.fetch_and_diffstat_all(package.name, &candidates) {
-                Ok(suggested_diff) => {
-                    total_lines += suggested_diff.diffstat.count;
-                    suggestions.push(SuggestItem {
-                        package: failure_idx,
-                        suggested_diff,
-                        suggested_criteria: audit_failure.criteria_failures.clone(),
-                        notable_parents,
-                    });
-                }
-                Err(err) => {
-                    // We don't want to actually error out completely here since other packages
-                    // might still successfully diff!
-                    error!("error diffing {}:{} {}", package.name, package.version, err);
+            let audits = match combined {
+                Some(combined)
+                    if separate.is_empty() || combined.diffstat.count <= separate_lines =>
+                {
+                    vec![(audit_failure.criteria_failures.clone(), combined)]
                 }
+                _ => separate,
+            };
+            if audits.is_empty() {
+                // ERRORS: we logged why above, but also list the packages we couldn't help with
+                unsuggested.push(failure_idx);
+                continue;
+            }
+            let audits_for_package = audits.len();
+            for (suggested_criteria, suggested_diff) in audits {
+                total_lines += suggested_diff.diffstat.count;
+                suggestions.push(SuggestItem {
+                    package: failure_idx,
+                    suggested_diff,
+                    suggested_criteria,
+                    notable_parents: notable_parents.clone(),
+                    audits_for_package,
+                });
             }
         }
 
@@ -2213,66 +3026,403 @@ impl<'a> ResolveReport<'a> {
         cache.command_history.last_suggest = last_suggest;
 
         Ok(Some(Suggest {
+            failures,
             suggestions,
+            unsuggested,
             suggestions_by_criteria,
             total_lines,
+            imports: vec![],
         }))
     }
 
-    /// Print a full human-readable report
-    pub fn print_human(&self, out: &mut dyn Write, cfg: &Config) -> Result<(), VetError> {
-        match &self.conclusion {
-            Conclusion::Success(res) => res.print_human(out, self, cfg),
-            Conclusion::FailForViolationConflict(res) => res.print_human(out, self, cfg),
-            Conclusion::FailForVet(res) => res.print_human(out, self, cfg),
+    /// Pick the alternative in `alternatives` that's cheapest to fix, returning its
+    /// index and cost. Ties go to the first (smallest) alternative.
+    ///
+    /// Dependencies that every alternative needs fixed the same way don't change
+    /// which one is cheapest, so they're left out of the comparison (and only
+    /// added back to the cost), and we stop costing an alternative once it's
+    /// no cheaper than the best so far. Both save us fetching crates we'd
+    /// never suggest auditing.
+    fn cheapest_alternative(
+        &self,
+        cache: &mut Cache,
+        allow_deltas: bool,
+        fix_costs: &mut FastMap<(PackageIdx, usize), u64>,
+        alternatives: &[FailedDeps],
+    ) -> (usize, u64) {
+        let mut common = alternatives.first().cloned().unwrap_or_default();
+        common.retain(|depidx, criteria| {
+            alternatives.iter().skip(1).all(|failed_deps| {
+                failed_deps
+                    .get(depidx)
+                    .is_some_and(|other| other.contains(criteria) && criteria.contains(other))
+            })
+        });
+
+        let mut best = (0, u64::MAX);
+        for (idx, failed_deps) in alternatives.iter().enumerate() {
+            let cost = self.fix_cost(
+                cache,
+                allow_deltas,
+                fix_costs,
+                failed_deps
+                    .iter()
+                    .filter(|(depidx, _)| !common.contains_key(depidx)),
+                best.1,
+            );
+            if cost < best.1 || idx == 0 {
+                best = (idx, cost);
+            }
         }
+        let common_cost = self.fix_cost(cache, allow_deltas, fix_costs, &common, u64::MAX);
+        (best.0, best.1.saturating_add(common_cost))
     }
 
-    /// Print only the suggest portion of a human-readable report
-    pub fn print_suggest_human(&self, out: &mut dyn Write, cfg: &Config) -> Result<(), VetError> {
-        if let Some(suggest) = self.compute_suggest(cfg, true)? {
-            suggest.print_human(out, self)?;
-        } else {
-            // This API is only used for vet-suggest
-            writeln!(out, "Nothing to suggest, you're fully audited!")?;
+    /// Roughly how many lines would need to be audited to fix all of `failed_deps`,
+    /// giving up as soon as that's at least `limit`.
+    ///
+    /// A package that's to blame for several criteria costs the sum over the
+    /// criteria that none of the others imply, as one audit for a stronger
+    /// criteria also covers the weaker ones it implies.
+    fn fix_cost<'f>(
+        &self,
+        cache: &mut Cache,
+        allow_deltas: bool,
+        fix_costs: &mut FastMap<(PackageIdx, usize), u64>,
+        failed_deps: impl IntoIterator<Item = (&'f PackageIdx, &'f CriteriaSet)>,
+        limit: u64,
+    ) -> u64 {
+        let mut total: u64 = 0;
+        for (&depidx, criteria) in failed_deps {
+            let search_results = &self.results[depidx].search_results;
+            let disconnected = criteria
+                .indices()
+                .filter(|&idx| matches!(search_results[idx], SearchResult::Disconnected { .. }))
+                .collect::<Vec<_>>();
+            let implies = |stronger: usize, weaker: usize| {
+                self.criteria_mapper.implied_criteria[stronger].has_criteria(weaker)
+            };
+            for criteria_idx in criteria.indices() {
+                let cost = match &search_results[criteria_idx] {
+                    SearchResult::Connected { .. } => continue,
+                    SearchResult::PossiblyConnected { .. } => {
+                        self.criteria_fix_cost(cache, allow_deltas, fix_costs, depidx, criteria_idx)
+                    }
+                    SearchResult::Disconnected { .. } => {
+                        // Weaker criteria get audited along with a stronger one
+                        if disconnected.iter().any(|&other| {
+                            implies(other, criteria_idx) && !implies(criteria_idx, other)
+                        }) {
+                            continue;
+                        }
+                        disconnected
+                            .iter()
+                            .filter(|&&other| implies(criteria_idx, other))
+                            .map(|&other| {
+                                self.criteria_fix_cost(
+                                    cache,
+                                    allow_deltas,
+                                    fix_costs,
+                                    depidx,
+                                    other,
+                                )
+                            })
+                            .max()
+                            .unwrap_or(0)
+                    }
+                };
+                total = total.saturating_add(cost);
+                if total >= limit {
+                    return total;
+                }
+            }
         }
-        Ok(())
+        total
     }
 
-    /// Print a full human-readable report
-    pub fn print_json(&self, out: &mut dyn Write, cfg: &Config) -> Result<(), VetError> {
-        let result = match &self.conclusion {
-            Conclusion::Success(success) => {
-                let json_package = |pkgidx: &PackageIdx| {
-                    let package = &self.graph.nodes[*pkgidx];
-                    json!({
-                        "name": package.name,
-                        "version": package.version,
-                    })
-                };
-                json!({
-                    "conclusion": "success",
-                    "vetted_fully": success.vetted_fully.iter().map(json_package).collect::<Vec<_>>(),
-                    "vetted_partially": success.vetted_partially.iter().map(json_package).collect::<Vec<_>>(),
-                    "vetted_with_unaudited": success.vetted_with_unaudited.iter().map(json_package).collect::<Vec<_>>(),
-                    "useless_unaudited": success.useless_unaudited.iter().map(json_package).collect::<Vec<_>>(),
-                })
+    /// Roughly how many lines would need to be audited to get `criteria_idx` for the
+    /// package `depidx`, memoized in `fix_costs`.
+    ///
+    /// Packages that are to blame themselves cost their cheapest suggested audit,
+    /// and packages that are blocked by their own dependencies cost whatever their
+    /// cheapest alternative does. Audits we can't diffstat cost `u64::MAX`.
+    fn criteria_fix_cost(
+        &self,
+        cache: &mut Cache,
+        allow_deltas: bool,
+        fix_costs: &mut FastMap<(PackageIdx, usize), u64>,
+        depidx: PackageIdx,
+        criteria_idx: usize,
+    ) -> u64 {
+        if let Some(&cost) = fix_costs.get(&(depidx, criteria_idx)) {
+            return cost;
+        }
+        let cost = match &self.results[depidx].search_results[criteria_idx] {
+            SearchResult::Connected { .. } => 0,
+            SearchResult::PossiblyConnected { alternatives } => {
+                self.cheapest_alternative(cache, allow_deltas, fix_costs, alternatives)
+                    .1
             }
-            Conclusion::FailForViolationConflict(fail) => json!({
-                "conclusion": "fail (violation)",
-                "violations": fail.violations.iter().map(|(pkgidx, violations)| {
-                    let package = &self.graph.nodes[*pkgidx];
-                    let key = format!("{}:{}", package.name, package.version);
-                    (key, violations)
-                }).collect::<SortedMap<_,_>>(),
-            }),
-            Conclusion::FailForVet(fail) => {
-                // Suggest output generally requires hitting the network.
-                let suggest = if cfg.cli.frozen {
-                    None
-                } else {
-                    self.compute_suggest(cfg, true)?
+            SearchResult::Disconnected {
+                reachable_from_root,
+                reachable_from_target,
+            } => {
+                let package = &self.graph.nodes[depidx];
+                let candidates =
+                    suggest_candidates(allow_deltas, reachable_from_root, reachable_from_target);
+                match cache.fetch_and_diffstat_all(package.name, &candidates) {
+                    Ok(suggested_diff) => suggested_diff.diffstat.count,
+                    Err(err) => {
+                        warn!("error diffing {}:{} {}", package.name, package.version, err);
+                        u64::MAX
+                    }
+                }
+            }
+        };
+        fix_costs.insert((depidx, criteria_idx), cost);
+        cost
+    }
+
+    /// Find audit sets in the registry that we don't import yet, and would vet
+    /// some of our failures, adding them to `suggest`.
+    ///
+    /// Problems fetching things are only warnings, as the rest of the
+    /// suggestions are still useful.
+    pub fn compute_import_suggest(
+        &self,
+        cfg: &Config,
+        source: &RegistrySource,
+        suggest: &mut Suggest,
+    ) -> Result<(), VetError> {
+        let _suggest_span = trace_span!("suggest imports").entered();
+        let store = source.store;
+        let registry = match storage::fetch_registry(cfg, source.registry) {
+            Ok(registry) => registry,
+            Err(e) => {
+                // ERRORS: buffered warning
+                warn!("couldn't check the registry for audit sets to import: {e}");
+                return Ok(());
+            }
+        };
+
+        let candidates = registry
+            .registry
+            .into_iter()
+            .filter(|(name, entry)| {
+                !store.config.imports.contains_key(name)
+                    && !store
+                        .config
+                        .imports
+                        .values()
+                        .any(|import| import.url == entry.url)
+            })
+            .map(|(name, entry)| {
+                // Only the built-in criteria, which is what `cargo vet import`
+                // offers to do.
+                let import = RemoteImport {
+                    url: entry.url,
+                    criteria_map: vec![],
+                    map_builtins: true,
+                    include: vec![],
+                    exclude: vec![],
+                    auditors: vec![],
+                    max_criteria: None,
+                    sha256: None,
+                };
+                (name, import)
+            })
+            .collect::<SortedMap<_, _>>();
+
+        let fetched = store.fetch_other_imports(cfg, &candidates);
+        for error in &fetched.errors {
+            // ERRORS: buffered warning
+            warn!("{error}");
+        }
+        for (name, audits) in fetched.imports.audits {
+            let import = candidates[&name].clone();
+            let url = import.url.clone();
+            let hypothetical = store.with_import(name.clone(), import, audits);
+            suggest.imports.extend(self.evaluate_import(
+                cfg,
+                &hypothetical,
+                source.guess_deeper,
+                name,
+                url,
+                &suggest.suggestions,
+            ));
+        }
+
+        suggest
+            .imports
+            .sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.name.cmp(&b.name)));
+        Ok(())
+    }
+
+    /// Work out which of our failures would be vetted if we resolved against
+    /// `hypothetical` instead (a copy of our store with `name` imported).
+    pub fn evaluate_import(
+        &self,
+        cfg: &Config,
+        hypothetical: &Store,
+        guess_deeper: bool,
+        name: ImportName,
+        url: String,
+        suggestions: &[SuggestItem],
+    ) -> Option<ImportSuggestion> {
+        let fail = match &self.conclusion {
+            Conclusion::FailForVet(fail) => fail,
+            _ => return None,
+        };
+        // The hypothetical store has the same config as ours, which we've
+        // already resolved, so this can't fail.
+        let report = resolve(
+            &cfg.metadata,
+            cfg.cli.filter_graph.as_ref(),
+            hypothetical,
+            guess_deeper,
+        )
+        .ok()?;
+        // The graph is the same, so package indices line up between reports.
+        let vetted = fail
+            .failures
+            .keys()
+            .copied()
+            .filter(|pkgidx| match &report.conclusion {
+                Conclusion::Success(_) => true,
+                Conclusion::FailForVet(fail) => !fail.failures.contains_key(pkgidx),
+                // If this brings in violations, it doesn't help
+                Conclusion::FailForViolationConflict(_) => false,
+            })
+            .collect::<Vec<_>>();
+        if vetted.is_empty() {
+            return None;
+        }
+        let lines = suggestions
+            .iter()
+            .filter(|item| vetted.contains(&item.package))
+            .map(|item| item.suggested_diff.diffstat.count)
+            .sum();
+        Some(ImportSuggestion {
+            name,
+            url,
+            vetted,
+            lines,
+        })
+    }
+
+    /// Print a full human-readable report
+    pub fn print_human(&self, out: &mut dyn Write, cfg: &Config) -> Result<(), VetError> {
+        match &self.conclusion {
+            Conclusion::Success(res) => res.print_human(out, self, cfg)?,
+            Conclusion::FailForViolationConflict(res) => res.print_human(out, self, cfg)?,
+            Conclusion::FailForVet(res) => res.print_human(out, self, cfg)?,
+        }
+        if !self.acknowledged_violations.is_empty() {
+            writeln!(out)?;
+            writeln!(
+                out,
+                "WARNING: using packages despite acknowledged violations:"
+            )?;
+            for (&pkgidx, acknowledged) in &self.acknowledged_violations {
+                let package = &self.graph.nodes[pkgidx];
+                writeln!(out, "  {}:{}", package.name, package.version)?;
+                for ack in acknowledged {
+                    for violation in &ack.conflicts {
+                        print_violation_conflict(out, violation)?;
+                    }
+                    writeln!(out, "    acknowledged because: {}", ack.justification)?;
+                    writeln!(out)?;
+                }
+            }
+        }
+        if self.filtered_foreign_audits != 0 {
+            writeln!(out)?;
+            writeln!(
+                out,
+                "note: ignored {} imported audits outside the scope of their imports",
+                self.filtered_foreign_audits
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Print only the suggest portion of a human-readable report, including
+    /// audit sets from `registry` that could be imported
+    pub fn print_suggest_human(
+        &self,
+        out: &mut dyn Write,
+        cfg: &Config,
+        registry: Option<&RegistrySource>,
+    ) -> Result<(), VetError> {
+        if let Some(mut suggest) = self.compute_suggest(cfg, true)? {
+            if let Some(registry) = registry {
+                self.compute_import_suggest(cfg, registry, &mut suggest)?;
+            }
+            suggest.print_human(out, self)?;
+        } else {
+            // This API is only used for vet-suggest
+            writeln!(out, "Nothing to suggest, you're fully audited!")?;
+        }
+        Ok(())
+    }
+
+    /// Print a full json report
+    pub fn print_json(&self, out: &mut dyn Write, cfg: &Config) -> Result<(), VetError> {
+        self.print_json_with(out, cfg, None)
+    }
+
+    /// Print a full json report, with suggestions including audit sets from
+    /// `registry` that could be imported
+    pub fn print_suggest_json(
+        &self,
+        out: &mut dyn Write,
+        cfg: &Config,
+        registry: Option<&RegistrySource>,
+    ) -> Result<(), VetError> {
+        self.print_json_with(out, cfg, registry)
+    }
+
+    fn print_json_with(
+        &self,
+        out: &mut dyn Write,
+        cfg: &Config,
+        registry: Option<&RegistrySource>,
+    ) -> Result<(), VetError> {
+        let mut result = match &self.conclusion {
+            Conclusion::Success(success) => {
+                let json_package = |pkgidx: &PackageIdx| {
+                    let package = &self.graph.nodes[*pkgidx];
+                    json!({
+                        "name": package.name,
+                        "version": package.version,
+                    })
+                };
+                json!({
+                    "conclusion": "success",
+                    "vetted_fully": success.vetted_fully.iter().map(json_package).collect::<Vec<_>>(),
+                    "vetted_partially": success.vetted_partially.iter().map(json_package).collect::<Vec<_>>(),
+                    "vetted_with_unaudited": success.vetted_with_unaudited.iter().map(json_package).collect::<Vec<_>>(),
+                    "useless_unaudited": success.useless_unaudited.iter().map(json_package).collect::<Vec<_>>(),
+                })
+            }
+            Conclusion::FailForViolationConflict(fail) => json!({
+                "conclusion": "fail (violation)",
+                "violations": fail.violations.iter().map(|(pkgidx, violations)| {
+                    let package = &self.graph.nodes[*pkgidx];
+                    let key = format!("{}:{}", package.name, package.version);
+                    (key, violations)
+                }).collect::<SortedMap<_,_>>(),
+            }),
+            Conclusion::FailForVet(fail) => {
+                // Suggest output generally requires hitting the network.
+                let mut suggest = if cfg.cli.frozen {
+                    None
+                } else {
+                    self.compute_suggest(cfg, true)?
                 };
+                if let (Some(suggest), Some(registry)) = (&mut suggest, registry) {
+                    self.compute_import_suggest(cfg, registry, suggest)?;
+                }
                 let json_suggest_item = |item: &SuggestItem| {
                     let package = &self.graph.nodes[item.package];
                     json!({
@@ -2280,11 +3430,15 @@ impl<'a> ResolveReport<'a> {
                         "notable_parents": item.notable_parents,
                         "suggested_criteria": self.criteria_mapper.criteria_names(&item.suggested_criteria).collect::<Vec<_>>(),
                         "suggested_diff": item.suggested_diff,
+                        "audits_for_package": item.audits_for_package,
                     })
                 };
+                let failures = suggest
+                    .as_ref()
+                    .map_or(&fail.failures, |suggest| &suggest.failures);
                 json!({
                     "conclusion": "fail (vetting)",
-                    "failures": fail.failures.iter().map(|(&pkgidx, audit_fail)| {
+                    "failures": failures.iter().map(|(&pkgidx, audit_fail)| {
                         let package = &self.graph.nodes[pkgidx];
                         json!({
                             "name": package.name,
@@ -2296,10 +3450,519 @@ impl<'a> ResolveReport<'a> {
                         "suggestions": suggest.suggestions.iter().map(json_suggest_item).collect::<Vec<_>>(),
                         "suggest_by_criteria": suggest.suggestions_by_criteria.iter().map(|(criteria, items)| (criteria, items.iter().map(json_suggest_item).collect::<Vec<_>>())).collect::<SortedMap<_,_>>(),
                         "total_lines": suggest.total_lines,
+                        "unsuggested": suggest.unsuggested.iter().map(|&pkgidx| {
+                            let package = &self.graph.nodes[pkgidx];
+                            json!({
+                                "name": package.name,
+                                "version": package.version,
+                            })
+                        }).collect::<Vec<_>>(),
+                        "imports": suggest.imports.iter().map(|import| json!({
+                            "name": import.name,
+                            "url": import.url,
+                            "vetted": import.vetted.iter().map(|&pkgidx| {
+                                let package = &self.graph.nodes[pkgidx];
+                                json!({
+                                    "name": package.name,
+                                    "version": package.version,
+                                })
+                            }).collect::<Vec<_>>(),
+                            "lines": import.lines,
+                        })).collect::<Vec<_>>(),
                     })),
                 })
             }
         };
+        result["acknowledged_violations"] = json!(self
+            .acknowledged_violations
+            .iter()
+            .map(|(&pkgidx, acknowledged)| {
+                let package = &self.graph.nodes[pkgidx];
+                let key = format!("{}:{}", package.name, package.version);
+                let acknowledged = acknowledged
+                    .iter()
+                    .map(|ack| {
+                        json!({
+                            "violations": ack.conflicts,
+                            "justification": ack.justification,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                (key, acknowledged)
+            })
+            .collect::<SortedMap<_, _>>());
+        result["filtered_foreign_audits"] = json!(self.filtered_foreign_audits);
+
+        serde_json::to_writer_pretty(out, &result)?;
+
+        Ok(())
+    }
+
+    /// Find every path from a root (or the dev-build of a workspace member) down
+    /// to any of `targets`, along with the criteria required at each step.
+    ///
+    /// This follows the same rules the resolver does: first-party packages with a
+    /// policy of their own are only checked against that policy, so paths start at
+    /// them rather than passing through them.
+    ///
+    /// The number of paths can grow exponentially with the depth of the graph, so
+    /// we stop after the first [`MAX_WHY_PATHS`][].
+    pub fn compute_why(&self, store: &Store, targets: &[PackageIdx]) -> DependencyPaths {
+        let config = &store.config;
+        let graph = &self.graph;
+        let build_deps = |pkgidx: PackageIdx| {
+            let package = &graph.nodes[pkgidx];
+            package
+                .normal_deps
+                .iter()
+                .chain(&package.build_deps)
+                .copied()
+        };
+
+        // Only bother walking into packages that lead to one of the targets
+        let mut reverse_deps = vec![vec![]; graph.nodes.len()];
+        for pkgidx in 0..graph.nodes.len() {
+            for depidx in build_deps(pkgidx) {
+                reverse_deps[depidx].push(pkgidx);
+            }
+        }
+        let mut leads_to_target = FastSet::new();
+        let mut worklist = targets.to_vec();
+        while let Some(pkgidx) = worklist.pop() {
+            if leads_to_target.insert(pkgidx) {
+                worklist.extend(&reverse_deps[pkgidx]);
+            }
+        }
+
+        // The packages that are checked against a policy of their own
+        let own_policy = |pkgidx: PackageIdx| {
+            let package = &graph.nodes[pkgidx];
+            if package.is_third_party {
+                return None;
+            }
+            match config
+                .policy
+                .get(package.name)
+                .and_then(|p| p.criteria.as_ref())
+            {
+                Some(criteria) => Some((
+                    self.criteria_mapper.criteria_from_list(criteria),
+                    RequirementReason::Policy,
+                )),
+                None if package.is_root => Some((
+                    self.criteria_mapper
+                        .criteria_from_list([format::DEFAULT_POLICY_CRITERIA]),
+                    RequirementReason::DefaultPolicy,
+                )),
+                None => None,
+            }
+        };
+
+        let mut search_stack = vec![];
+        for &pkgidx in &graph.topo_index {
+            let package = &graph.nodes[pkgidx];
+            if let Some((criteria, reason)) = own_policy(pkgidx) {
+                if leads_to_target.contains(&pkgidx) {
+                    search_stack.push(DependencyPath {
+                        dev: false,
+                        steps: vec![DependencyStep {
+                            package: pkgidx,
+                            criteria,
+                            reason,
+                        }],
+                    });
+                }
+            }
+            if package.is_workspace_member
+                && package
+                    .dev_deps
+                    .iter()
+                    .any(|depidx| leads_to_target.contains(depidx))
+            {
+                let (criteria, reason) = match config
+                    .policy
+                    .get(package.name)
+                    .and_then(|p| p.dev_criteria.as_ref())
+                {
+                    Some(criteria) => (
+                        self.criteria_mapper.criteria_from_list(criteria),
+                        RequirementReason::Policy,
+                    ),
+                    None => (
+                        self.criteria_mapper
+                            .criteria_from_list([format::DEFAULT_POLICY_DEV_CRITERIA]),
+                        RequirementReason::DefaultPolicy,
+                    ),
+                };
+                search_stack.push(DependencyPath {
+                    dev: true,
+                    steps: vec![DependencyStep {
+                        package: pkgidx,
+                        criteria,
+                        reason,
+                    }],
+                });
+            }
+        }
+        // Pop the paths in the order we found the roots
+        search_stack.reverse();
+
+        // Depth-first search, keeping the whole path so far on the stack. The graph
+        // is acyclic apart from dev-deps, but don't trust that.
+        let mut paths = vec![];
+        let mut truncated = false;
+        while let Some(path) = search_stack.pop() {
+            let last = path.steps.last().unwrap();
+            let package = &graph.nodes[last.package];
+            if targets.contains(&last.package) && !(path.dev && path.steps.len() == 1) {
+                if paths.len() == MAX_WHY_PATHS {
+                    truncated = true;
+                    break;
+                }
+                paths.push(path.clone());
+            }
+
+            let deps: Vec<PackageIdx> = if path.dev && path.steps.len() == 1 {
+                package.dev_deps.clone()
+            } else {
+                build_deps(last.package).collect()
+            };
+            let mut children = vec![];
+            for depidx in deps {
+                if !leads_to_target.contains(&depidx)
+                    || own_policy(depidx).is_some()
+                    || path.steps.iter().any(|step| step.package == depidx)
+                {
+                    continue;
+                }
+                let (criteria, reason) = self.required_of_dep(store, last, depidx);
+                if criteria.is_empty() {
+                    continue;
+                }
+                let mut child = path.clone();
+                child.steps.push(DependencyStep {
+                    package: depidx,
+                    criteria,
+                    reason,
+                });
+                children.push(child);
+            }
+            // Visit the children in order
+            search_stack.extend(children.into_iter().rev());
+        }
+        DependencyPaths { paths, truncated }
+    }
+
+    /// The criteria that `parent` requires of its dependency `depidx`.
+    fn required_of_dep(
+        &self,
+        store: &Store,
+        parent: &DependencyStep,
+        depidx: PackageIdx,
+    ) -> (CriteriaSet, RequirementReason) {
+        let package = &self.graph.nodes[parent.package];
+        let dep_name = self.graph.nodes[depidx].name;
+        if !package.is_third_party {
+            // A policy's dependency-criteria replaces whatever we would otherwise need
+            let overridden = store
+                .config
+                .policy
+                .get(package.name)
+                .and_then(|policy| policy.dependency_criteria.get(dep_name));
+            return match overridden {
+                Some(criteria) => (
+                    self.criteria_mapper.criteria_from_list(criteria),
+                    RequirementReason::PolicyDependencyCriteria,
+                ),
+                None => (parent.criteria.clone(), RequirementReason::Inherited),
+            };
+        }
+
+        // Otherwise it's up to the audits we used to vet each criteria
+        let mut required = self.criteria_mapper.no_criteria();
+        let mut reason = RequirementReason::Inherited;
+        for criteria_idx in parent.criteria.indices() {
+            let implied = &self.criteria_mapper.implied_criteria[criteria_idx];
+            let path = match &self.results[parent.package].search_results[criteria_idx] {
+                SearchResult::Connected { path, .. } => &path[..],
+                _ => &[],
+            };
+            if path.is_empty() {
+                required.unioned_with(implied);
+            }
+            for step in path {
+                match step.dependency_criteria(store, &self.criteria_mapper, dep_name) {
+                    Some(criteria) => {
+                        required.unioned_with(&criteria);
+                        reason = RequirementReason::AuditDependencyCriteria;
+                    }
+                    None => required.unioned_with(implied),
+                }
+            }
+        }
+        (required, reason)
+    }
+
+    /// Print the paths from `compute_why`, one step per line
+    pub fn print_why_human(
+        &self,
+        out: &mut dyn Write,
+        package: PackageStr,
+        paths: &DependencyPaths,
+    ) -> Result<(), VetError> {
+        if paths.paths.is_empty() {
+            writeln!(
+                out,
+                "{package} isn't required by any root or workspace member"
+            )?;
+            return Ok(());
+        }
+        for (path_idx, path) in paths.paths.iter().enumerate() {
+            if path_idx != 0 {
+                writeln!(out)?;
+            }
+            for (depth, step) in path.steps.iter().enumerate() {
+                let node = &self.graph.nodes[step.package];
+                let dev = if path.dev && depth == 0 { " (dev)" } else { "" };
+                let reason = match step.reason {
+                    RequirementReason::Policy if path.dev => {
+                        " (dev-criteria in its policy)".to_owned()
+                    }
+                    RequirementReason::Policy => " (criteria in its policy)".to_owned(),
+                    RequirementReason::DefaultPolicy if path.dev => {
+                        " (default dev policy)".to_owned()
+                    }
+                    RequirementReason::DefaultPolicy => " (default root policy)".to_owned(),
+                    RequirementReason::Inherited => String::new(),
+                    RequirementReason::PolicyDependencyCriteria => format!(
+                        " (dependency-criteria in the policy of {})",
+                        self.graph.nodes[path.steps[depth - 1].package].name
+                    ),
+                    RequirementReason::AuditDependencyCriteria => format!(
+                        " (dependency-criteria in the audits of {})",
+                        self.graph.nodes[path.steps[depth - 1].package].name
+                    ),
+                };
+                writeln!(
+                    out,
+                    "{:indent$}{}:{}{dev} needs {}{reason}",
+                    "",
+                    node.name,
+                    node.version,
+                    self.criteria_mapper
+                        .criteria_names(&step.criteria)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    indent = depth * 2,
+                )?;
+            }
+        }
+        if paths.truncated {
+            writeln!(out)?;
+            writeln!(
+                out,
+                "(only showing the first {MAX_WHY_PATHS} paths to {package})"
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Print the paths from `compute_why` as json
+    pub fn print_why_json(
+        &self,
+        out: &mut dyn Write,
+        package: PackageStr,
+        paths: &DependencyPaths,
+    ) -> Result<(), VetError> {
+        let result = json!({
+            "name": package,
+            "truncated": paths.truncated,
+            "paths": paths.paths.iter().map(|path| json!({
+                "dev": path.dev,
+                "steps": path.steps.iter().map(|step| {
+                    let node = &self.graph.nodes[step.package];
+                    json!({
+                        "name": node.name,
+                        "version": node.version,
+                        "criteria": self.criteria_mapper.criteria_names(&step.criteria).collect::<Vec<_>>(),
+                        "reason": match step.reason {
+                            RequirementReason::Policy => "policy",
+                            RequirementReason::DefaultPolicy => "default policy",
+                            RequirementReason::Inherited => "inherited",
+                            RequirementReason::PolicyDependencyCriteria => "policy dependency-criteria",
+                            RequirementReason::AuditDependencyCriteria => "audit dependency-criteria",
+                        },
+                    })
+                }).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+        });
+
+        serde_json::to_writer_pretty(out, &result)?;
+
+        Ok(())
+    }
+
+    /// Print, for each criteria, the audits that vet (or fail to vet) `pkgidx`
+    pub fn print_explain_human(
+        &self,
+        out: &mut dyn Write,
+        pkgidx: PackageIdx,
+    ) -> Result<(), VetError> {
+        let package = &self.graph.nodes[pkgidx];
+        let result = &self.results[pkgidx];
+        writeln!(out, "{}:{}", package.name, package.version)?;
+        if result.search_results.is_empty() {
+            writeln!(out, "  not checked by cargo-vet")?;
+            return Ok(());
+        }
+        for (criteria_idx, search_result) in result.search_results.iter().enumerate() {
+            let criteria_name = &self.criteria_mapper.list[criteria_idx].0;
+            match search_result {
+                SearchResult::Connected {
+                    fully_audited,
+                    path,
+                } => {
+                    if path.is_empty() {
+                        writeln!(out, "  {criteria_name}: vetted through its dependencies")?;
+                    } else if *fully_audited {
+                        writeln!(out, "  {criteria_name}: vetted")?;
+                    } else {
+                        writeln!(out, "  {criteria_name}: vetted with 'unaudited' entries")?;
+                    }
+                    for step in path {
+                        writeln!(out, "    {}", describe_audit_step(step))?;
+                    }
+                }
+                SearchResult::PossiblyConnected { alternatives } => {
+                    writeln!(
+                        out,
+                        "  {criteria_name}: not vetted, because of its dependencies"
+                    )?;
+                    for (alternative_idx, failed_deps) in alternatives.iter().enumerate() {
+                        if alternatives.len() > 1 {
+                            let label = if alternative_idx == 0 { "either" } else { "or" };
+                            writeln!(out, "    {label}:")?;
+                        }
+                        let indent = if alternatives.len() > 1 { 6 } else { 4 };
+                        for (&depidx, criteria) in failed_deps {
+                            let dep = &self.graph.nodes[depidx];
+                            writeln!(
+                                out,
+                                "{:indent$}{}:{} is missing {}",
+                                "",
+                                dep.name,
+                                dep.version,
+                                self.criteria_mapper
+                                    .criteria_names(criteria)
+                                    .collect::<Vec<_>>()
+                                    .join(", ")
+                            )?;
+                        }
+                    }
+                }
+                SearchResult::Disconnected {
+                    reachable_from_root,
+                    reachable_from_target,
+                } => {
+                    let join = |versions: &SortedSet<&Version>| {
+                        versions
+                            .iter()
+                            .map(|v| v.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    };
+                    writeln!(out, "  {criteria_name}: not vetted, no audits connect")?;
+                    writeln!(
+                        out,
+                        "    reachable from {ROOT_VERSION}: {}",
+                        join(reachable_from_root)
+                    )?;
+                    writeln!(
+                        out,
+                        "    reachable from {}: {}",
+                        package.version,
+                        join(reachable_from_target)
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Print the same information as `print_explain_human`, as json
+    pub fn print_explain_json(
+        &self,
+        out: &mut dyn Write,
+        pkgidx: PackageIdx,
+    ) -> Result<(), VetError> {
+        let package = &self.graph.nodes[pkgidx];
+        let result = &self.results[pkgidx];
+        let json_step = |step: &AuditStep| {
+            let (source, entry_who) = match step.origin {
+                DeltaOrigin::OwnAudit(entry) => (json!("audits"), json!(entry.who)),
+                DeltaOrigin::ForeignAudit(import, entry) => {
+                    (json!({ "import": import }), json!(entry.who))
+                }
+                DeltaOrigin::Unaudited(_) => (json!("unaudited"), json!(null)),
+            };
+            let kind = match step.origin {
+                DeltaOrigin::Unaudited(_) => "unaudited",
+                _ if *step.from == ROOT_VERSION => "full",
+                _ => "delta",
+            };
+            json!({
+                "kind": kind,
+                "from": step.from,
+                "to": step.to,
+                "source": source,
+                "who": entry_who,
+            })
+        };
+        let criteria = result
+            .search_results
+            .iter()
+            .enumerate()
+            .map(|(criteria_idx, search_result)| {
+                let criteria_name = &self.criteria_mapper.list[criteria_idx].0;
+                let explanation = match search_result {
+                    SearchResult::Connected {
+                        fully_audited,
+                        path,
+                    } => json!({
+                        "status": "vetted",
+                        "fully_audited": fully_audited,
+                        "chain": path.iter().map(json_step).collect::<Vec<_>>(),
+                    }),
+                    SearchResult::PossiblyConnected { alternatives } => json!({
+                        "status": "failed dependencies",
+                        "alternatives": alternatives.iter().map(|failed_deps| {
+                            failed_deps.iter().map(|(&depidx, criteria)| {
+                                let dep = &self.graph.nodes[depidx];
+                                json!({
+                                    "name": dep.name,
+                                    "version": dep.version,
+                                    "missing_criteria": self.criteria_mapper.criteria_names(criteria).collect::<Vec<_>>(),
+                                })
+                            }).collect::<Vec<_>>()
+                        }).collect::<Vec<_>>(),
+                    }),
+                    SearchResult::Disconnected {
+                        reachable_from_root,
+                        reachable_from_target,
+                    } => json!({
+                        "status": "disconnected",
+                        "reachable_from_root": reachable_from_root,
+                        "reachable_from_target": reachable_from_target,
+                    }),
+                };
+                (criteria_name, explanation)
+            })
+            .collect::<SortedMap<_, _>>();
+        let result = json!({
+            "name": package.name,
+            "version": package.version,
+            "criteria": criteria,
+        });
 
         serde_json::to_writer_pretty(out, &result)?;
 
@@ -2307,6 +3970,76 @@ impl<'a> ResolveReport<'a> {
     }
 }
 
+/// The audits that would connect the versions reachable from 0.0.0 with those
+/// reachable from the version we need. Without deltas, only full audits.
+fn suggest_candidates(
+    allow_deltas: bool,
+    from_root: &SortedSet<&Version>,
+    from_target: &SortedSet<&Version>,
+) -> SortedSet<Delta> {
+    let mut candidates = SortedSet::new();
+    if allow_deltas {
+        // If we're allowed deltas than try to find a bridge from src and dest
+        for &dest in from_target {
+            let mut closest_above = None;
+            let mut closest_below = None;
+            for &src in from_root {
+                if src < dest {
+                    if let Some(closest) = closest_below {
+                        if src > closest {
+                            closest_below = Some(src);
+                        }
+                    } else {
+                        closest_below = Some(src);
+                    }
+                } else if let Some(closest) = closest_above {
+                    if src < closest {
+                        closest_above = Some(src);
+                    }
+                } else {
+                    closest_above = Some(src);
+                }
+            }
+
+            for closest in closest_below.into_iter().chain(closest_above) {
+                candidates.insert(Delta {
+                    from: closest.clone(),
+                    to: dest.clone(),
+                });
+            }
+        }
+    } else {
+        // If we're not allowing deltas, just try everything reachable from the target
+        for &dest in from_target {
+            candidates.insert(Delta {
+                from: ROOT_VERSION.clone(),
+                to: dest.clone(),
+            });
+        }
+    }
+    candidates
+}
+
+/// A one-line description of an audit in a chain, and where it came from.
+fn describe_audit_step(step: &AuditStep) -> String {
+    let (entry, source) = match step.origin {
+        DeltaOrigin::OwnAudit(entry) => (entry, "audits.toml".to_owned()),
+        DeltaOrigin::ForeignAudit(import, entry) => (entry, format!("import {import}")),
+        DeltaOrigin::Unaudited(_) => {
+            return format!("unaudited {} (config.toml)", step.to);
+        }
+    };
+    let audit = if *step.from == ROOT_VERSION {
+        format!("full audit of {}", step.to)
+    } else {
+        format!("delta audit {} -> {}", step.from, step.to)
+    };
+    match &entry.who {
+        Some(who) => format!("{audit} ({source}, by {who})"),
+        None => format!("{audit} ({source})"),
+    }
+}
+
 impl Success {
     pub fn print_human(
         &self,
@@ -2395,10 +4128,21 @@ impl Suggest {
                             )
                         },
                         format!("(used by {})", item.notable_parents),
-                        if item.suggested_diff.from == ROOT_VERSION {
-                            format!("({} lines)", item.suggested_diff.diffstat.count)
-                        } else {
-                            format!("({})", item.suggested_diff.diffstat.raw.trim())
+                        match (
+                            item.suggested_diff.from == ROOT_VERSION,
+                            item.audits_for_package,
+                        ) {
+                            (true, 1) => format!("({} lines)", item.suggested_diff.diffstat.count),
+                            (false, 1) => format!("({})", item.suggested_diff.diffstat.raw.trim()),
+                            (true, count) => format!(
+                                "({} lines, one of {count} audits needed for {})",
+                                item.suggested_diff.diffstat.count, package.name
+                            ),
+                            (false, count) => format!(
+                                "({}, one of {count} audits needed for {})",
+                                item.suggested_diff.diffstat.raw.trim(),
+                                package.name
+                            ),
                         },
                     )
                 })
@@ -2422,6 +4166,51 @@ impl Suggest {
             writeln!(out)?;
         }
 
+        if !self.unsuggested.is_empty() {
+            writeln!(out, "could not suggest audits for (see the errors above):")?;
+            for &pkgidx in &self.unsuggested {
+                let package = &report.graph.nodes[pkgidx];
+                writeln!(out, "    {}:{}", package.name, package.version)?;
+            }
+            writeln!(out)?;
+        }
+
+        if !self.imports.is_empty() {
+            writeln!(out, "recommended imports from the registry:")?;
+
+            let strings = self
+                .imports
+                .iter()
+                .map(|import| {
+                    let mut vetted = import
+                        .vetted
+                        .iter()
+                        .map(|&pkgidx| report.graph.nodes[pkgidx].name)
+                        .collect::<Vec<_>>();
+                    vetted.sort();
+                    vetted.dedup();
+                    (
+                        format!("cargo vet import {}", import.name),
+                        format!("(vets {})", vetted.join(", ")),
+                        format!("(saves {} lines)", import.lines),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            let max0 = strings.iter().max_by_key(|s| s.0.len()).unwrap().0.len();
+            let max1 = strings.iter().max_by_key(|s| s.1.len()).unwrap().1.len();
+            for (s0, s1, s2) in strings {
+                writeln!(
+                    out,
+                    "    {s0:width0$}  {s1:width1$}  {s2}",
+                    width0 = max0,
+                    width1 = max1,
+                )?;
+            }
+
+            writeln!(out)?;
+        }
+
         writeln!(out, "estimated audit backlog: {} lines", self.total_lines)?;
         writeln!(out)?;
         writeln!(out, "Use |cargo vet certify| to record the audits.")?;
@@ -2437,11 +4226,21 @@ impl FailForVet {
         report: &ResolveReport,
         cfg: &Config,
     ) -> Result<(), VetError> {
+        // Suggest output generally requires hitting the network. When we have it, it
+        // blames whichever dependencies are cheapest to fix, so report those.
+        let suggest = if cfg.cli.frozen {
+            None
+        } else {
+            report.compute_suggest(cfg, true)?
+        };
+        let failures = suggest
+            .as_ref()
+            .map_or(&self.failures, |suggest| &suggest.failures);
+
         writeln!(out, "Vetting Failed!")?;
         writeln!(out)?;
-        writeln!(out, "{} unvetted dependencies:", self.failures.len())?;
-        let mut failures = self
-            .failures
+        writeln!(out, "{} unvetted dependencies:", failures.len())?;
+        let mut failures = failures
             .iter()
             .map(|(&failed_idx, failure)| (&report.graph.nodes[failed_idx], failure))
             .collect::<Vec<_>>();
@@ -2459,12 +4258,9 @@ impl FailForVet {
             )?;
         }
 
-        // Suggest output generally requires hitting the network.
-        if !cfg.cli.frozen {
-            if let Some(suggest) = report.compute_suggest(cfg, true)? {
-                writeln!(out)?;
-                suggest.print_human(out, report)?;
-            }
+        if let Some(suggest) = suggest {
+            writeln!(out)?;
+            suggest.print_human(out, report)?;
         }
 
         Ok(())
@@ -2484,57 +4280,66 @@ impl FailForViolationConflict {
             let package = &report.graph.nodes[pkgidx];
             writeln!(out, "  {}:{}", package.name, package.version)?;
             for violation in violations {
-                match violation {
-                    ViolationConflict::CurVersionConflict { source, violation } => {
-                        write!(out, "    this version is forbidden by ")?;
-                        print_entry(out, source, violation)?;
-                    }
-                    ViolationConflict::AuditConflict {
-                        violation_source,
-                        violation,
-                        audit_source,
-                        audit,
-                    } => {
-                        write!(out, "    the ")?;
-                        print_entry(out, audit_source, audit)?;
-                        write!(out, "    conflicts with ")?;
-                        print_entry(out, violation_source, violation)?;
-                    }
-                }
+                print_violation_conflict(out, violation)?;
                 writeln!(out)?;
             }
         }
 
-        fn print_entry(
-            out: &mut dyn Write,
-            source: &AuditSource,
-            entry: &AuditEntry,
-        ) -> Result<(), VetError> {
-            match source {
-                AuditSource::OwnAudits => write!(out, "own ")?,
-                AuditSource::Foreign(name) => write!(out, "foreign ({name}) ")?,
-            }
-            match &entry.kind {
-                AuditKind::Full { version, .. } => {
-                    writeln!(out, "audit {version}")?;
-                }
-                AuditKind::Delta { delta, .. } => {
-                    writeln!(out, "audit {} -> {}", delta.from, delta.to)?;
-                }
-                AuditKind::Violation { violation } => {
-                    writeln!(out, "violation against {violation}")?;
-                }
+        Ok(())
+    }
+}
+
+fn print_violation_conflict(
+    out: &mut dyn Write,
+    violation: &ViolationConflict,
+) -> Result<(), VetError> {
+    match violation {
+        ViolationConflict::CurVersionConflict { source, violation } => {
+            write!(out, "    this version is forbidden by ")?;
+            print_entry(out, source, violation)?;
+        }
+        ViolationConflict::AuditConflict {
+            violation_source,
+            violation,
+            audit_source,
+            audit,
+        } => {
+            write!(out, "    the ")?;
+            print_entry(out, audit_source, audit)?;
+            write!(out, "    conflicts with ")?;
+            print_entry(out, violation_source, violation)?;
+        }
+    }
+
+    fn print_entry(
+        out: &mut dyn Write,
+        source: &AuditSource,
+        entry: &AuditEntry,
+    ) -> Result<(), VetError> {
+        match source {
+            AuditSource::OwnAudits => write!(out, "own ")?,
+            AuditSource::Foreign(name) => write!(out, "foreign ({name}) ")?,
+        }
+        match &entry.kind {
+            AuditKind::Full { version, .. } => {
+                writeln!(out, "audit {version}")?;
             }
-            writeln!(out, "      criteria: {:?}", entry.criteria)?;
-            if let Some(who) = &entry.who {
-                writeln!(out, "      who: {who}")?;
+            AuditKind::Delta { delta, .. } => {
+                writeln!(out, "audit {} -> {}", delta.from, delta.to)?;
             }
-            if let Some(notes) = &entry.notes {
-                writeln!(out, "      notes: {notes}")?;
+            AuditKind::Violation { violation } => {
+                writeln!(out, "violation against {violation}")?;
             }
-            Ok(())
         }
-
+        writeln!(out, "      criteria: {:?}", entry.criteria)?;
+        if let Some(who) = &entry.who {
+            writeln!(out, "      who: {who}")?;
+        }
+        if let Some(notes) = &entry.notes {
+            writeln!(out, "      notes: {notes}")?;
+        }
         Ok(())
     }
+
+    Ok(())
 }
@@ -1,26 +1,35 @@
+use core::fmt;
 use std::{
     ffi::OsString,
     fs::{self, File, OpenOptions},
     io::{self, BufReader, Read, Seek, Write},
     mem,
+    ops::Range,
     path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use cargo_metadata::Version;
+use cargo_metadata::{Metadata, Version};
+use cargo_platform::Platform;
 use eyre::Context;
 use flate2::read::GzDecoder;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tar::Archive;
 use tracing::{error, log::warn, trace, trace_span};
 
 use crate::{
+    editor,
     flock::{FileLock, Filesystem},
     format::{
-        AuditsFile, CommandHistory, ConfigFile, Delta, DiffCache, DiffStat, ImportsFile,
-        MetaConfig, PackageStr, SortedMap, SortedSet,
+        AuditEntry, AuditKind, AuditsFile, CommandHistory, ConfigFile, CriteriaEntry, CriteriaName,
+        CriteriaStr, Delta, DiffCache, DiffStat, FastMap, FastSet, ForeignCriteriaName, ImportName,
+        ImportsFile, MetaConfig, PackageStr, RegistryFile, RemoteImport, SortedMap, SortedSet,
+        FORMAT_VERSION, SAFE_TO_DEPLOY, SAFE_TO_RUN,
     },
     resolver::{self, DiffRecommendation},
-    Config, PartialConfig, VetError,
+    targets, Config, PackageExt, PartialConfig, VetError,
 };
 
 // tmp cache for various shenanigans
@@ -42,42 +51,149 @@ pub static DEFAULT_STORE: &str = "supply-chain";
 static AUDITS_TOML: &str = "audits.toml";
 static CONFIG_TOML: &str = "config.toml";
 static IMPORTS_LOCK: &str = "imports.lock";
+static REGISTRY_TOML: &str = "registry.toml";
+
+/// The registry of well-known audit sets that `cargo vet import` uses by default.
+pub static DEFAULT_REGISTRY: &str =
+    "https://raw.githubusercontent.com/bholley/cargo-vet/main/registry.toml";
+
+/// Build a [`TomlPath`][] out of keys and array indices.
+macro_rules! toml_path {
+    ($($segment:expr),* $(,)?) => {
+        vec![$(TomlPathSegment::from($segment)),*]
+    };
+}
 
 struct StoreLock {
     config: FileLock,
+    // Whether this is only a shared lock, in which case we mustn't write.
+    read_only: bool,
 }
 
 impl StoreLock {
-    fn new(store: &Filesystem) -> Result<Self, VetError> {
-        Ok(StoreLock {
-            config: store.open_rw(CONFIG_TOML, "vet store")?,
-        })
+    fn new(store: &Filesystem, read_only: bool) -> Result<Self, VetError> {
+        loop {
+            let config = if read_only {
+                store.open_ro(CONFIG_TOML, "vet store")?
+            } else {
+                store.open_rw(CONFIG_TOML, "vet store")?
+            };
+            // `Store::commit` replaces config.toml by renaming a new file over
+            // it. If that happened while we were waiting for the lock, we now
+            // hold a lock on a file that isn't part of the store anymore, so
+            // try again with the new one.
+            if is_same_file(config.file(), config.path())? {
+                return Ok(StoreLock { config, read_only });
+            }
+        }
     }
     fn read_config(&self) -> io::Result<impl Read + '_> {
         let mut file = self.config.file();
         file.rewind()?;
         Ok(file)
     }
-    fn write_config(&self) -> io::Result<impl Write + '_> {
-        let mut file = self.config.file();
-        file.rewind()?;
-        file.set_len(0)?;
-        Ok(file)
-    }
     fn read_audits(&self) -> io::Result<impl Read> {
         File::open(self.config.parent().join(AUDITS_TOML))
     }
-    fn write_audits(&self) -> io::Result<impl Write> {
-        File::create(self.config.parent().join(AUDITS_TOML))
-    }
     fn read_imports(&self) -> io::Result<impl Read> {
         File::open(self.config.parent().join(IMPORTS_LOCK))
     }
-    fn write_imports(&self) -> io::Result<impl Write> {
-        File::create(self.config.parent().join(IMPORTS_LOCK))
+
+    /// Replace the given files in the store with new contents.
+    ///
+    /// Each file is first written out in full to a temporary sibling, and
+    /// only once they've all been written are they renamed into place. If
+    /// anything goes wrong before that point the old store is untouched.
+    /// config.toml is renamed last, as it's what everyone else is locking on.
+    ///
+    /// Each rename is atomic, but the renames as a group aren't: if one fails
+    /// (or we crash) part way through, the store is left with a mix of old
+    /// and new files. Leftover temporary files are removed on error.
+    fn write_files(&self, files: [(&str, Vec<u8>); 3]) -> Result<(), VetError> {
+        let dir = self.config.parent();
+        let mut temps = Vec::new();
+        for (name, contents) in files {
+            let path = dir.join(name);
+            let temp_path = dir.join(format!(".{name}.tmp"));
+            let written = OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&temp_path)
+                .and_then(|mut temp| {
+                    temp.write_all(&contents)?;
+                    temp.sync_all()
+                });
+            if let Err(e) = written {
+                let _ = fs::remove_file(&temp_path);
+                remove_temps(&temps);
+                return Err(eyre::Report::new(e)
+                    .wrap_err(format!("failed to write `{}`", temp_path.display())));
+            }
+            temps.push((temp_path, path));
+        }
+        let mut replaced = Vec::new();
+        for (idx, (temp_path, path)) in temps.iter().enumerate() {
+            if let Err(e) = fs::rename(temp_path, path) {
+                remove_temps(&temps[idx..]);
+                let mut error = eyre::Report::new(e)
+                    .wrap_err(format!("failed to replace `{}`", path.display()));
+                if !replaced.is_empty() {
+                    error = error.wrap_err(format!(
+                        "the store was only partially updated (already replaced: {})",
+                        replaced.join(", ")
+                    ));
+                }
+                return Err(error);
+            }
+            replaced.push(path.file_name().unwrap().to_string_lossy());
+        }
+        sync_dir(dir).wrap_err_with(|| format!("failed to sync `{}`", dir.display()))?;
+        Ok(())
+    }
+}
+
+/// Best-effort cleanup of the temporary files of a failed [`StoreLock::write_files`][].
+fn remove_temps(temps: &[(PathBuf, PathBuf)]) {
+    for (temp_path, _) in temps {
+        let _ = fs::remove_file(temp_path);
     }
 }
 
+/// Make renames in `dir` durable.
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    File::open(dir)?.sync_all()
+}
+
+/// Make renames in `dir` durable.
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> io::Result<()> {
+    // Directories can't be opened like this on windows, and renames are
+    // already journaled by NTFS.
+    Ok(())
+}
+
+/// Check whether `file` is (still) the file at `path`.
+#[cfg(unix)]
+fn is_same_file(file: &File, path: &Path) -> io::Result<bool> {
+    use std::os::unix::fs::MetadataExt;
+
+    let locked = file.metadata()?;
+    match fs::metadata(path) {
+        Ok(current) => Ok(locked.dev() == current.dev() && locked.ino() == current.ino()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Check whether `file` is (still) the file at `path`.
+#[cfg(not(unix))]
+fn is_same_file(_file: &File, _path: &Path) -> io::Result<bool> {
+    // FIXME: compare file indices on windows
+    Ok(true)
+}
+
 /// The store (typically `supply-chain/`)
 ///
 /// All access to this directory should be managed by this type to avoid races.
@@ -87,13 +203,23 @@ impl StoreLock {
 ///
 /// To write back this value, use [`Store::commit`][].
 pub struct Store {
-    // Exclusive file lock held for the config file
+    // File lock held for the config file (exclusive, unless read-only)
     lock: Option<StoreLock>,
 
     // Contents of the store, eagerly loaded and already validated.
     pub config: ConfigFile,
     pub imports: ImportsFile,
     pub audits: AuditsFile,
+
+    // The raw text of the files as they were loaded, so that diagnostics can
+    // point at the offending line. Absent for mock stores and for anything
+    // we've replaced since loading (e.g. freshly fetched imports).
+    config_src: Option<String>,
+    audits_src: Option<String>,
+    imports_src: Option<String>,
+
+    // If any of the files were in an older format, the oldest version.
+    migrated_from: Option<u64>,
 }
 
 impl Store {
@@ -102,23 +228,31 @@ impl Store {
         let root = cfg.metacfg.store_path();
         root.create_dir()?;
 
-        let lock = StoreLock::new(&root)?;
+        let lock = StoreLock::new(&root, false)?;
 
         Ok(Self {
             lock: Some(lock),
             config: ConfigFile {
                 default_criteria: String::new(),
+                allow_stale_imports: false,
+                targets: None,
                 imports: SortedMap::new(),
                 policy: SortedMap::new(),
                 unaudited: SortedMap::new(),
+                acknowledged_violations: SortedMap::new(),
             },
             imports: ImportsFile {
+                fetched: SortedMap::new(),
                 audits: SortedMap::new(),
             },
             audits: AuditsFile {
                 criteria: SortedMap::new(),
                 audits: SortedMap::new(),
             },
+            config_src: None,
+            audits_src: None,
+            imports_src: None,
+            migrated_from: None,
         })
     }
 
@@ -129,29 +263,76 @@ impl Store {
 
     /// Acquire an existing store
     pub fn acquire(cfg: &Config) -> Result<Self, VetError> {
-        let root = cfg.metacfg.store_path();
+        let store = Self::load(cfg, false)?;
+        store.validate_for(cfg)?;
+        Ok(store)
+    }
+
+    /// Acquire an existing store for reading only
+    ///
+    /// This only takes a shared lock, so any number of read-only commands can
+    /// run at once, but the store can't be committed.
+    pub fn acquire_ro(cfg: &Config) -> Result<Self, VetError> {
+        let store = Self::load(cfg, true)?;
+        store.validate_for(cfg)?;
+        Ok(store)
+    }
 
-        // Before we do anything else, acquire an exclusive lock on the
-        // config.toml file in the store.
-        // XXX: Consider acquiring a non-exclusive lock in cases where an
-        // exclusive one isn't needed.
-        let lock = StoreLock::new(&root)?;
+    /// Acquire an existing store without validating it
+    ///
+    /// This is only for commands which are about to repair the store themselves
+    /// (e.g. `regenerate-unaudited`), which must validate it before using it.
+    pub fn acquire_unvalidated(cfg: &Config) -> Result<Self, VetError> {
+        Self::load(cfg, false)
+    }
 
-        let config: ConfigFile = load_toml(lock.read_config()?)?;
-        let audits: AuditsFile = load_toml(lock.read_audits()?)?;
-        let imports: ImportsFile = load_toml(lock.read_imports()?)?;
+    fn load(cfg: &Config, read_only: bool) -> Result<Self, VetError> {
+        let root = cfg.metacfg.store_path();
 
-        let store = Self {
+        // Before we do anything else, acquire a lock on the config.toml file
+        // in the store.
+        let lock = StoreLock::new(&root, read_only)?;
+
+        let (mut config, config_src, config_version) =
+            load_store_toml(CONFIG_TOML, lock.read_config()?)?;
+        migrate_config(&mut config, &config_src, config_version);
+        let (audits, audits_src, audits_version) =
+            load_store_toml(AUDITS_TOML, lock.read_audits()?)?;
+        let (imports, imports_src, imports_version) =
+            load_store_toml(IMPORTS_LOCK, lock.read_imports()?)?;
+
+        let version = config_version.min(audits_version).min(imports_version);
+        let migrated_from = (version < FORMAT_VERSION).then_some(version);
+        if let Some(version) = migrated_from {
+            // ERRORS: commands that write to the store upgrade it as a side effect,
+            // which shouldn't go unmentioned any more than not upgrading it does
+            let upgrade = if read_only {
+                "run `cargo vet migrate` to upgrade it"
+            } else {
+                "it will be upgraded when it's written"
+            };
+            warn!(
+                "the store is in an old format (version {version}, the current one is {FORMAT_VERSION}), {upgrade}"
+            );
+        }
+
+        Ok(Self {
             lock: Some(lock),
             config,
             audits,
             imports,
-        };
-
-        // Check that the store isn't corrupt
-        store.validate()?;
+            config_src: Some(config_src),
+            audits_src: Some(audits_src),
+            imports_src: Some(imports_src),
+            migrated_from,
+        })
+    }
 
-        Ok(store)
+    /// Check that the store isn't corrupt. We can only check entries against the
+    /// dependency graph if we're looking at the whole thing.
+    fn validate_for(&self, cfg: &Config) -> Result<(), StoreValidateErrors> {
+        let full_graph = !cfg.cli.features.no_all_features && cfg.cli.filter_graph.is_none();
+        self.validate(full_graph.then_some(&cfg.metadata))
     }
 
     /// Create a mock store
@@ -162,79 +343,1630 @@ impl Store {
             config,
             imports,
             audits,
+            config_src: None,
+            audits_src: None,
+            imports_src: None,
+            migrated_from: None,
         }
     }
 
+    /// Create a mock store from the text of its files
+    #[cfg(test)]
+    pub fn mock_from_sources(config: &str, audits: &str, imports: &str) -> Result<Self, VetError> {
+        let (mut config, config_src, config_version) =
+            parse_store_toml(CONFIG_TOML, config.to_owned())?;
+        migrate_config(&mut config, &config_src, config_version);
+        let (audits, audits_src, audits_version) =
+            parse_store_toml(AUDITS_TOML, audits.to_owned())?;
+        let (imports, imports_src, imports_version) =
+            parse_store_toml(IMPORTS_LOCK, imports.to_owned())?;
+        let version = config_version.min(audits_version).min(imports_version);
+        Ok(Self {
+            lock: None,
+            config,
+            audits,
+            imports,
+            config_src: Some(config_src),
+            audits_src: Some(audits_src),
+            imports_src: Some(imports_src),
+            migrated_from: (version < FORMAT_VERSION).then_some(version),
+        })
+    }
+
+    /// If the store was in an older format when it was loaded, the oldest
+    /// version it was in. It will be in the current format once committed.
+    pub fn migrated_from(&self) -> Option<u64> {
+        self.migrated_from
+    }
+
     /// Commit the store's contents back to disk
+    ///
+    /// The new files are written out in full before any of the old ones are
+    /// replaced, so a failure to write leaves the store as it was. Replacing
+    /// the files isn't atomic as a whole though, see [`StoreLock::write_files`][].
+    ///
+    /// Only the entries of config.toml and audits.toml which actually changed
+    /// are rewritten, everything else (comments included) is left as it was.
     pub fn commit(self) -> Result<(), VetError> {
-        // TODO: make this truly transactional?
-        // (With a dir rename? Does that work with the lock? Fine because it's already closed?)
-        if let Some(lock) = self.lock {
-            let audits = lock.write_audits()?;
-            let config = lock.write_config()?;
-            let imports = lock.write_imports()?;
-            store_audits(audits, self.audits)?;
-            store_config(config, self.config)?;
-            store_imports(imports, self.imports)?;
+        self.commit_with(Layout::Preserve)
+    }
+
+    /// Commit the store's contents back to disk, putting config.toml and
+    /// audits.toml back into the usual layout (but keeping their comments).
+    pub fn commit_reformatted(self) -> Result<(), VetError> {
+        self.commit_with(Layout::Reformat)
+    }
+
+    fn commit_with(mut self, layout: Layout) -> Result<(), VetError> {
+        if let Some(lock) = self.lock.take() {
+            if lock.read_only {
+                return Err(eyre::eyre!(
+                    "tried to commit a store that was acquired read-only"
+                ));
+            }
+            lock.write_files(self.serialize(layout)?)?;
         }
         Ok(())
     }
 
-    /// Validate the store's integrity
-    pub fn validate(&self) -> Result<(), VetError> {
-        // ERRORS: ideally these are all gathered diagnostics, want to report as many errors
-        // at once as possible!
+    /// Get the contents of the files which committing the store would write.
+    ///
+    /// Everything is serialized before touching the disk, so that a
+    /// serialization failure can't leave the store half-written.
+    fn serialize(self, layout: Layout) -> Result<[(&'static str, Vec<u8>); 3], VetError> {
+        let mut audits = Vec::new();
+        let mut config = Vec::new();
+        let mut imports = Vec::new();
+        let audits_src = self.audits_src.as_deref().map(|src| (src, layout));
+        let config_src = self.config_src.as_deref().map(|src| (src, layout));
+        store_audits(&mut audits, self.audits, audits_src)?;
+        store_config(&mut config, self.config, config_src)?;
+        store_imports(&mut imports, self.imports)?;
+        // config.toml goes last, see `StoreLock::write_files`
+        Ok([
+            (AUDITS_TOML, audits),
+            (IMPORTS_LOCK, imports),
+            (CONFIG_TOML, config),
+        ])
+    }
 
-        // TODO(#66): implement validation
-        //
-        // * check that policy entries are only first-party
-        // * check that unaudited entries are for things that exist?
-        // * check that lockfile and imports aren't desync'd (catch new/removed import urls)
+    /// Get the contents of the files which committing a mock store would write
+    #[cfg(test)]
+    pub fn mock_commit(self, reformat: bool) -> SortedMap<&'static str, String> {
+        let layout = if reformat {
+            Layout::Reformat
+        } else {
+            Layout::Preserve
+        };
+        self.serialize(layout)
+            .unwrap()
+            .into_iter()
+            .map(|(file, contents)| (file, String::from_utf8(contents).unwrap()))
+            .collect()
+    }
+
+    /// Validate the store's integrity
+    ///
+    /// All problems are gathered up and reported together, so that someone
+    /// fixing up a hand-edited store doesn't have to play whack-a-mole.
+    ///
+    /// If `metadata` is provided, entries which refer to packages are also
+    /// checked against the dependency graph. This should only be done when
+    /// the graph is complete (no feature or graph filtering), otherwise we'd
+    /// complain about entries for packages that are just hidden right now.
+    pub fn validate(&self, metadata: Option<&Metadata>) -> Result<(), StoreValidateErrors> {
+        // TODO(#66): remaining validation
         //
-        // * check that each CriteriaEntry has 'description' or 'description_url'
-        // * check that no one is trying to shadow builtin criteria (safe-to-run, safe-to-deploy)
-        // * check that all criteria are valid in:
-        //   * CriteriaEntry::implies
-        //   * AuditEntry::criteria
-        //   * DependencyCriteria
-        // * check that all 'audits' entries are well-formed
         // * check that all package names are valid (with crates.io...?)
         // * check that all reviews have a 'who' (currently an Option to stub it out)
-        // * catch no-op deltas?
-        Ok(())
+        let mut errors = Vec::new();
+
+        // Criteria available to our own files
+        let own_criteria = valid_criteria(&self.audits);
+        let mut lints = Vec::new();
+        validate_audits_file(
+            &mut errors,
+            &mut lints,
+            AUDITS_TOML,
+            "",
+            &[],
+            &self.audits,
+            &own_criteria,
+        );
+        // Our own files should be tidy
+        errors.append(&mut lints);
+
+        // config.toml, which can only refer to our own criteria
+        let config = &self.config;
+        check_criteria(
+            &mut errors,
+            CONFIG_TOML,
+            toml_path!["default-criteria"],
+            &own_criteria,
+            &config.default_criteria,
+            || "`default-criteria`".to_string(),
+        );
+        check_target_triples(
+            &mut errors,
+            &[],
+            "targets",
+            config.targets.as_deref(),
+            || "`targets`".to_string(),
+        );
+        for (package, policy) in &config.policy {
+            let policy_path = toml_path!["policy", package];
+            check_target_triples(
+                &mut errors,
+                &policy_path,
+                "targets",
+                policy.targets.as_deref(),
+                || format!("policy for `{package}` (targets)"),
+            );
+            check_target_triples(
+                &mut errors,
+                &policy_path,
+                "dev-targets",
+                policy.dev_targets.as_deref(),
+                || format!("policy for `{package}` (dev-targets)"),
+            );
+            let criteria = policy.criteria.iter().flatten().enumerate();
+            let criteria = criteria.map(|(idx, c)| ("criteria", idx, c));
+            let dev_criteria = policy.dev_criteria.iter().flatten().enumerate();
+            let dev_criteria = dev_criteria.map(|(idx, c)| ("dev-criteria", idx, c));
+            for (key, idx, criteria) in criteria.chain(dev_criteria) {
+                check_criteria(
+                    &mut errors,
+                    CONFIG_TOML,
+                    toml_path!["policy", package, key, idx],
+                    &own_criteria,
+                    criteria,
+                    || format!("policy for `{package}`"),
+                );
+            }
+            for (dep, criteria) in &policy.dependency_criteria {
+                for (idx, criteria) in criteria.iter().enumerate() {
+                    check_criteria(
+                        &mut errors,
+                        CONFIG_TOML,
+                        toml_path!["policy", package, "dependency-criteria", dep, idx],
+                        &own_criteria,
+                        criteria,
+                        || format!("policy for `{package}` (dependency-criteria for `{dep}`)"),
+                    );
+                }
+            }
+        }
+        for (package, entries) in &config.unaudited {
+            for (entry_idx, entry) in entries.iter().enumerate() {
+                check_criteria(
+                    &mut errors,
+                    CONFIG_TOML,
+                    toml_path!["unaudited", package, entry_idx, "criteria"],
+                    &own_criteria,
+                    &entry.criteria,
+                    || format!("unaudited entry for `{package}:{}`", entry.version),
+                );
+                for (dep, criteria) in &entry.dependency_criteria {
+                    for (idx, criteria) in criteria.iter().enumerate() {
+                        check_criteria(
+                            &mut errors,
+                            CONFIG_TOML,
+                            toml_path![
+                                "unaudited",
+                                package,
+                                entry_idx,
+                                "dependency-criteria",
+                                dep,
+                                idx
+                            ],
+                            &own_criteria,
+                            criteria,
+                            || {
+                                format!(
+                                    "unaudited entry for `{package}:{}` (dependency-criteria for `{dep}`)",
+                                    entry.version
+                                )
+                            },
+                        );
+                    }
+                }
+            }
+        }
+
+        // Imported audits, which live in their own criteria namespace
+        for (import_name, import) in &config.imports {
+            if let Some(sha256) = &import.sha256 {
+                let valid = sha256.len() == 64
+                    && sha256
+                        .chars()
+                        .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c));
+                if !valid {
+                    errors.push(StoreValidateError::new(
+                        CONFIG_TOML,
+                        toml_path!["imports", import_name, "sha256"],
+                        format!(
+                            "import `{import_name}` has an invalid sha256 (expected 64 lowercase hex digits)"
+                        ),
+                    ));
+                }
+            }
+            if let Some(max_criteria) = &import.max_criteria {
+                check_criteria(
+                    &mut errors,
+                    CONFIG_TOML,
+                    toml_path!["imports", import_name, "max-criteria"],
+                    &own_criteria,
+                    max_criteria,
+                    || format!("max-criteria for import `{import_name}`"),
+                );
+            }
+            let foreign_audits = self.imports.audits.get(import_name);
+            let foreign_criteria = foreign_audits.map(valid_criteria);
+            for (mapping_idx, mapping) in import.criteria_map.iter().enumerate() {
+                let mapping_path = toml_path!["imports", import_name, "criteria-map", mapping_idx];
+                check_criteria(
+                    &mut errors,
+                    CONFIG_TOML,
+                    join_path(&mapping_path, toml_path!["ours"]),
+                    &own_criteria,
+                    &mapping.ours,
+                    || format!("criteria-map for import `{import_name}`"),
+                );
+                if let Some(foreign_criteria) = &foreign_criteria {
+                    for (idx, theirs) in mapping.theirs.iter().enumerate() {
+                        check_criteria(
+                            &mut errors,
+                            CONFIG_TOML,
+                            join_path(&mapping_path, toml_path!["theirs", idx]),
+                            foreign_criteria,
+                            theirs,
+                            || {
+                                format!(
+                                    "criteria-map for import `{import_name}` (foreign criteria)"
+                                )
+                            },
+                        );
+                    }
+                }
+            }
+        }
+        for (package, acknowledged) in &config.acknowledged_violations {
+            for (idx, ack) in acknowledged.iter().enumerate() {
+                let ack_path = toml_path!["acknowledged-violations", package, idx];
+                if !config.imports.contains_key(&ack.import) {
+                    errors.push(StoreValidateError::new(
+                        CONFIG_TOML,
+                        join_path(&ack_path, toml_path!["import"]),
+                        format!(
+                            "acknowledged violation for `{package}` refers to import `{}`, which isn't in your imports",
+                            ack.import
+                        ),
+                    ));
+                }
+                if ack.justification.trim().is_empty() {
+                    errors.push(StoreValidateError::new(
+                        CONFIG_TOML,
+                        join_path(&ack_path, toml_path!["justification"]),
+                        format!("acknowledged violation for `{package}` needs a justification"),
+                    ));
+                }
+            }
+        }
+        for (import_name, foreign_audits) in &self.imports.audits {
+            let import_path = toml_path!["audits", import_name];
+            if !config.imports.contains_key(import_name) {
+                errors.push(StoreValidateError::new(
+                    IMPORTS_LOCK,
+                    import_path.clone(),
+                    format!(
+                        "audits for import `{import_name}` are locked, but it isn't in config.toml's imports (run `cargo vet fetch-imports`)"
+                    ),
+                ));
+            }
+            // Tidiness is our peers' business, so those problems are only
+            // warnings. Anything that stops us understanding their audits is
+            // still an error though: skipping an entry we can't read could
+            // mean skipping one of their violations.
+            let prefix = format!("import `{import_name}`: ");
+            let foreign_criteria = valid_criteria(foreign_audits);
+            validate_audits_file(
+                &mut errors,
+                &mut lints,
+                IMPORTS_LOCK,
+                &prefix,
+                &import_path,
+                foreign_audits,
+                &foreign_criteria,
+            );
+        }
+
+        // Things that need to know what packages are actually in the graph
+        if let Some(metadata) = metadata {
+            let packages = metadata
+                .packages
+                .iter()
+                .map(|package| (&*package.name, package.is_third_party()))
+                .collect::<FastMap<_, _>>();
+            for package in config.policy.keys() {
+                let message = match packages.get(&**package) {
+                    Some(false) => continue,
+                    Some(true) => format!(
+                        "policy for `{package}`, but policies only apply to first-party packages"
+                    ),
+                    None => format!("policy for `{package}`, which isn't one of your packages"),
+                };
+                errors.push(StoreValidateError::new(
+                    CONFIG_TOML,
+                    toml_path!["policy", package],
+                    message,
+                ));
+            }
+            for package in config.unaudited.keys() {
+                let message = match packages.get(&**package) {
+                    Some(true) => continue,
+                    Some(false) => format!(
+                        "unaudited entry for `{package}`, but first-party packages don't need audits"
+                    ),
+                    None => format!(
+                        "unaudited entry for `{package}`, which isn't one of your dependencies (run `cargo vet regenerate-unaudited`)"
+                    ),
+                };
+                errors.push(StoreValidateError::new(
+                    CONFIG_TOML,
+                    toml_path!["unaudited", package],
+                    message,
+                ));
+            }
+        }
+
+        self.locate_errors(&mut lints);
+        for lint in &lints {
+            // ERRORS: immediate warning
+            warn!("{lint}");
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            self.locate_errors(&mut errors);
+            Err(StoreValidateErrors { errors })
+        }
+    }
+
+    /// Point each error at the place in its file that caused it, if we still
+    /// have the text that the store was loaded from.
+    fn locate_errors(&self, errors: &mut [StoreValidateError]) {
+        let sources = [
+            (CONFIG_TOML, &self.config_src),
+            (AUDITS_TOML, &self.audits_src),
+            (IMPORTS_LOCK, &self.imports_src),
+        ];
+        for (file, src) in sources {
+            let src = match src {
+                Some(src) => src,
+                None => continue,
+            };
+            // If this somehow doesn't parse, the errors just won't have locations
+            let doc = match toml_edit::ImDocument::parse(&**src) {
+                Ok(doc) => doc,
+                Err(_) => continue,
+            };
+            for error in errors.iter_mut().filter(|error| error.file == file) {
+                error.location =
+                    find_span(&doc, &error.path).map(|span| SourceLocation::from_span(src, span));
+            }
+        }
+    }
+
+    /// A copy of this store with another import added, to see what importing
+    /// it would do. This copy isn't locked, so it can't be committed.
+    pub fn with_import(&self, name: ImportName, import: RemoteImport, audits: AuditsFile) -> Self {
+        let mut config = self.config.clone();
+        config.imports.insert(name.clone(), import);
+        let mut imports = self.imports.clone();
+        imports.audits.insert(name, audits);
+        Self {
+            lock: None,
+            config,
+            imports,
+            audits: self.audits.clone(),
+            config_src: None,
+            audits_src: None,
+            imports_src: None,
+            migrated_from: None,
+        }
     }
 
     /// Fetch foreign audits, only call this is we're not --locked
-    pub fn fetch_foreign_audits(&mut self) -> Result<(), VetError> {
-        let mut audits = SortedMap::new();
-        for (name, import) in &self.config.imports {
-            let url = &import.url;
-            // FIXME: this should probably be async but that's a Whole Thing and these files are small.
-            let audit_txt = reqwest::blocking::get(url).and_then(|r| r.text());
-            if let Err(e) = audit_txt {
-                // ERRORS: gathered diagnostic, we should find all the fetch errors at once
-                return Err(eyre::eyre!("Could not load {name} @ {url} - {e}"));
+    ///
+    /// This fails if any foreign criteria that we map to our own have changed
+    /// their definition since we last fetched, in which case the user should
+    /// review the changes with `cargo vet accept-criteria-change`.
+    pub fn fetch_foreign_audits(&mut self, cfg: &Config) -> Result<(), VetError> {
+        let new_imports = self.fetch_imports_or_stale(cfg)?;
+        self.update_imports(new_imports, false)?;
+        Ok(())
+    }
+
+    /// Fetch foreign audits like [`Store::fetch_foreign_audits`][], but accept
+    /// any changes to the definitions of their criteria, returning them.
+    pub fn accept_criteria_changes(
+        &mut self,
+        cfg: &Config,
+    ) -> Result<Vec<CriteriaChange>, VetError> {
+        let new_imports = self.fetch_imports_or_stale(cfg)?;
+        self.update_imports(new_imports, true)
+    }
+
+    /// Fetch our imports, falling back to the copies in imports.lock for any
+    /// that we can't fetch if we're allowed to use stale imports.
+    fn fetch_imports_or_stale(&self, cfg: &Config) -> Result<ImportsFile, VetError> {
+        let timeout = Duration::from_secs(cfg.cli.fetch_timeout);
+        let FetchedImports {
+            imports: mut new_imports,
+            errors,
+            ..
+        } = self.fetch_imports(timeout, true);
+
+        if errors.is_empty() {
+            return Ok(new_imports);
+        }
+        if !cfg.cli.allow_stale_imports && !self.config.allow_stale_imports {
+            return Err(ImportFetchErrors { errors }.into());
+        }
+
+        // We can only fall back to imports that we've fetched before, and only if
+        // we couldn't reach them. If we got something that doesn't match the pin,
+        // or isn't an audits.toml, that's something to look into.
+        let (stale, errors): (Vec<_>, Vec<_>) = errors.into_iter().partition(|error| {
+            matches!(error.failure, ImportFetchFailure::Load(_))
+                && self.imports.audits.contains_key(&error.import_name)
+        });
+        if !errors.is_empty() {
+            return Err(ImportFetchErrors { errors }.into());
+        }
+        let now = unix_now();
+        for error in stale {
+            let fetched = self.imports.fetched.get(&error.import_name).copied();
+            let age = match fetched {
+                Some(fetched) => format!("fetched {}", format_age(now.saturating_sub(fetched))),
+                None => "fetched at an unknown time".to_owned(),
+            };
+            // ERRORS: buffered warning
+            warn!(
+                "{error}\n  using the copy of `{}` in imports.lock ({age})",
+                error.import_name
+            );
+            let audits = self.imports.audits[&error.import_name].clone();
+            if let Some(fetched) = fetched {
+                new_imports
+                    .fetched
+                    .insert(error.import_name.clone(), fetched);
+            }
+            new_imports.audits.insert(error.import_name, audits);
+        }
+        Ok(new_imports)
+    }
+
+    /// Fetch our imports and pin each of them to the sha256 of what we got,
+    /// returning the imports whose pins changed (and what they were before).
+    pub fn pin_imports(
+        &mut self,
+        cfg: &Config,
+    ) -> Result<Vec<(ImportName, Option<String>)>, VetError> {
+        let timeout = Duration::from_secs(cfg.cli.fetch_timeout);
+        let FetchedImports {
+            imports,
+            hashes,
+            errors,
+        } = self.fetch_imports(timeout, false);
+        if !errors.is_empty() {
+            return Err(ImportFetchErrors { errors }.into());
+        }
+
+        let mut changed = vec![];
+        for (name, hash) in hashes {
+            let import = self.config.imports.get_mut(&name).unwrap();
+            if import.sha256.as_ref() != Some(&hash) {
+                changed.push((name, import.sha256.replace(hash)));
+            }
+        }
+        self.update_imports(imports, false)?;
+        Ok(changed)
+    }
+
+    /// Fetch all of our imports at once, so that one slow host doesn't hold up
+    /// the rest, returning the ones we got and errors for the ones we didn't.
+    ///
+    /// If `check_pins` is set, imports that don't match their `sha256` are
+    /// errors.
+    pub(crate) fn fetch_imports(&self, timeout: Duration, check_pins: bool) -> FetchedImports {
+        self.fetch_these_imports(&self.config.imports, timeout, check_pins)
+    }
+
+    /// Fetch some imports that aren't (necessarily) ours, like audit sets from
+    /// the registry that we're considering importing.
+    pub(crate) fn fetch_other_imports(
+        &self,
+        cfg: &PartialConfig,
+        imports: &SortedMap<ImportName, RemoteImport>,
+    ) -> FetchedImports {
+        let timeout = Duration::from_secs(cfg.cli.fetch_timeout);
+        self.fetch_these_imports(imports, timeout, false)
+    }
+
+    fn fetch_these_imports(
+        &self,
+        imports: &SortedMap<ImportName, RemoteImport>,
+        timeout: Duration,
+        check_pins: bool,
+    ) -> FetchedImports {
+        let client = match reqwest::blocking::Client::builder()
+            .timeout(timeout)
+            .build()
+        {
+            Ok(client) => client,
+            Err(e) => {
+                let errors = imports
+                    .iter()
+                    .map(|(name, import)| ImportFetchError {
+                        import_name: name.clone(),
+                        url: import.url.clone(),
+                        failure: ImportFetchFailure::Load(e.to_string()),
+                    })
+                    .collect();
+                return FetchedImports {
+                    imports: ImportsFile {
+                        fetched: SortedMap::new(),
+                        audits: SortedMap::new(),
+                    },
+                    hashes: SortedMap::new(),
+                    errors,
+                };
             }
-            let audit_file: Result<AuditsFile, _> = toml::from_str(&audit_txt.unwrap());
-            if let Err(e) = audit_file {
-                // ERRORS: gathered diagnostic, we should find all the fetch errors at once
-                return Err(eyre::eyre!("Could not parse {name} @ {url} - {e}"));
+        };
+
+        // Imports can be paths relative to the store. Mock stores don't have a
+        // directory, so those are relative to the current one.
+        let store_dir = self
+            .lock
+            .as_ref()
+            .and_then(|lock| lock.config.path().parent())
+            .unwrap_or_else(|| Path::new("."));
+
+        let results: Vec<_> = thread::scope(|scope| {
+            let handles: Vec<_> = imports
+                .iter()
+                .map(|(name, import)| {
+                    let client = &client;
+                    (
+                        name,
+                        import,
+                        scope.spawn(move || fetch_import(client, store_dir, import, check_pins)),
+                    )
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|(name, import, handle)| {
+                    let result = handle.join().unwrap_or_else(|_| {
+                        Err(ImportFetchFailure::Load("fetch panicked".to_owned()))
+                    });
+                    (name, import, result)
+                })
+                .collect()
+        });
+
+        let now = unix_now();
+        let mut fetched = SortedMap::new();
+        let mut audits = SortedMap::new();
+        let mut hashes = SortedMap::new();
+        let mut errors = vec![];
+        for (name, import, result) in results {
+            match result {
+                Ok((audit_file, hash)) => {
+                    fetched.insert(name.clone(), now);
+                    audits.insert(name.clone(), audit_file);
+                    hashes.insert(name.clone(), hash);
+                }
+                Err(failure) => errors.push(ImportFetchError {
+                    import_name: name.clone(),
+                    url: import.url.clone(),
+                    failure,
+                }),
             }
-            audits.insert(name.clone(), audit_file.unwrap());
+        }
+        FetchedImports {
+            imports: ImportsFile { fetched, audits },
+            hashes,
+            errors,
+        }
+    }
+
+    /// Replace our imports with freshly fetched ones, checking that none of
+    /// the foreign criteria we map have changed their definition (unless
+    /// we're accepting such changes).
+    pub(crate) fn update_imports(
+        &mut self,
+        mut new_imports: ImportsFile,
+        accept_changes: bool,
+    ) -> Result<Vec<CriteriaChange>, VetError> {
+        let changes = criteria_changes(&self.config, &self.imports, &new_imports);
+        if !changes.is_empty() && !accept_changes {
+            return Err(CriteriaChangeErrors { changes }.into());
         }
 
-        let new_imports = ImportsFile { audits };
-        // TODO(#68): error out if the criteria changed
+        // An import that hasn't changed keeps the time we first fetched it, so
+        // that imports.lock isn't rewritten every time we fetch.
+        for (name, audits) in &new_imports.audits {
+            if self.imports.audits.get(name) == Some(audits) {
+                if let Some(&fetched) = self.imports.fetched.get(name) {
+                    new_imports.fetched.insert(name.clone(), fetched);
+                }
+            }
+        }
 
         // Accept the new imports. These will only be committed if the current command succeeds.
         self.imports = new_imports;
 
+        self.imports_src = None;
+
         // Now do one last validation to catch corrupt imports
-        self.validate()?;
+        self.validate(None)?;
+        Ok(changes)
+    }
+}
+
+/// The current time, in seconds since the unix epoch.
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Roughly how long ago something happened, for humans.
+fn format_age(secs: u64) -> String {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+    let (count, unit) = if secs >= DAY {
+        (secs / DAY, "day")
+    } else if secs >= HOUR {
+        (secs / HOUR, "hour")
+    } else if secs >= MINUTE {
+        (secs / MINUTE, "minute")
+    } else {
+        return "just now".to_owned();
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{count} {unit}{plural} ago")
+}
+
+/// The SHA-256 of `data`, as lowercase hex.
+pub(crate) fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn fetch_import(
+    client: &reqwest::blocking::Client,
+    store_dir: &Path,
+    import: &RemoteImport,
+    check_pin: bool,
+) -> Result<(AuditsFile, String), ImportFetchFailure> {
+    let audit_bytes =
+        fetch_bytes(client, store_dir, &import.url).map_err(ImportFetchFailure::Load)?;
+
+    let hash = sha256_hex(&audit_bytes);
+    if let (true, Some(pin)) = (check_pin, &import.sha256) {
+        if *pin != hash {
+            return Err(ImportFetchFailure::Mismatch(format!(
+                "expected sha256 {pin}, but got {hash} (if this change is expected, re-pin it with `cargo vet fetch-imports --pin`)"
+            )));
+        }
+    }
+
+    let audit_txt =
+        String::from_utf8(audit_bytes).map_err(|e| ImportFetchFailure::Parse(e.to_string()))?;
+    let (audit_file, _, _) = parse_store_toml::<AuditsFile>(AUDITS_TOML, audit_txt)
+        .map_err(|e| ImportFetchFailure::Parse(e.to_string()))?;
+    if import.map_builtins {
+        check_builtins_not_redefined(&audit_file)?;
+    }
+    Ok((audit_file, hash))
+}
+
+/// Foreign audits are mapped to ours on the assumption that their built-in
+/// criteria mean the same thing as ours, so imports that map them (and sources
+/// we aggregate) mustn't redefine them.
+fn check_builtins_not_redefined(audits: &AuditsFile) -> Result<(), ImportFetchFailure> {
+    for builtin in [SAFE_TO_DEPLOY, SAFE_TO_RUN] {
+        if audits.criteria.contains_key(builtin) {
+            return Err(ImportFetchFailure::Parse(format!(
+                "it redefines the built-in criteria `{builtin}`"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Fetch the contents of `url`, which may also be a local file (see
+/// [`local_path`][]).
+fn fetch_bytes(
+    client: &reqwest::blocking::Client,
+    base_dir: &Path,
+    url: &str,
+) -> Result<Vec<u8>, String> {
+    match local_path(base_dir, url)? {
+        Some(path) => fs::read(&path).map_err(|e| format!("{}: {e}", path.display())),
+        None => client
+            .get(url)
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.bytes())
+            .map(|bytes| bytes.to_vec())
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// The local file a URL refers to, if it isn't one for us to download.
+///
+/// That's either a `file://` URL, or a plain path, which is relative to
+/// `base_dir` (the store directory, for imports).
+fn local_path(base_dir: &Path, url: &str) -> Result<Option<PathBuf>, String> {
+    if url.starts_with("file://") {
+        let path = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|url| url.to_file_path().ok())
+            .ok_or_else(|| "not a valid file:// URL".to_owned())?;
+        Ok(Some(path))
+    } else if url.contains("://") {
+        Ok(None)
+    } else {
+        Ok(Some(base_dir.join(url)))
+    }
+}
+
+/// Fetch a registry of well-known audit sets, from a URL or a local path
+/// (relative to the current directory).
+pub fn fetch_registry(cfg: &PartialConfig, registry: &str) -> Result<RegistryFile, VetError> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(cfg.cli.fetch_timeout))
+        .build()?;
+    let registry_bytes = fetch_bytes(&client, Path::new("."), registry)
+        // ERRORS: immediate fatal diagnostic
+        .map_err(|e| eyre::eyre!("could not load registry @ {registry} - {e}"))?;
+    let registry_txt = String::from_utf8(registry_bytes)
+        .map_err(|e| eyre::eyre!("could not parse registry @ {registry} - {e}"))?;
+    Ok(parse_toml(REGISTRY_TOML, &registry_txt)?)
+}
+
+/// Fetch the audits.toml files listed in `sources_path` (see `cargo vet
+/// aggregate`), and merge them into one.
+pub fn aggregate(cfg: &PartialConfig, sources_path: &Path) -> Result<AuditsFile, VetError> {
+    let sources_txt = fs::read_to_string(sources_path)
+        // ERRORS: immediate fatal diagnostic
+        .wrap_err_with(|| format!("could not read {}", sources_path.display()))?;
+    let sources = sources_txt
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect::<Vec<_>>();
+    let base_dir = sources_path.parent().unwrap_or_else(|| Path::new("."));
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(cfg.cli.fetch_timeout))
+        .build()?;
+    let results: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = sources
+            .iter()
+            .map(|&url| {
+                let client = &client;
+                (
+                    url,
+                    scope.spawn(move || fetch_aggregate_source(client, base_dir, url)),
+                )
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|(url, handle)| {
+                let result = handle
+                    .join()
+                    .unwrap_or_else(|_| Err(ImportFetchFailure::Load("fetch panicked".to_owned())));
+                (url, result)
+            })
+            .collect()
+    });
+
+    let mut fetched = vec![];
+    let mut errors = vec![];
+    for (url, result) in results {
+        match result {
+            Ok(audits) => fetched.push((url.to_owned(), audits)),
+            Err(failure) => errors.push(AggregateSourceError {
+                url: url.to_owned(),
+                failure,
+            }),
+        }
+    }
+    if !errors.is_empty() {
+        // ERRORS: immediate fatal diagnostic
+        return Err(AggregateSourceErrors { errors }.into());
+    }
+
+    Ok(merge_audits(fetched)?)
+}
+
+fn fetch_aggregate_source(
+    client: &reqwest::blocking::Client,
+    base_dir: &Path,
+    url: &str,
+) -> Result<AuditsFile, ImportFetchFailure> {
+    let audit_bytes = fetch_bytes(client, base_dir, url).map_err(ImportFetchFailure::Load)?;
+    let audit_txt =
+        String::from_utf8(audit_bytes).map_err(|e| ImportFetchFailure::Parse(e.to_string()))?;
+    let (audit_file, _, _) = parse_store_toml::<AuditsFile>(AUDITS_TOML, audit_txt)
+        .map_err(|e| ImportFetchFailure::Parse(e.to_string()))?;
+    check_builtins_not_redefined(&audit_file)?;
+    Ok(audit_file)
+}
+
+/// Merge several audits.toml files, given with where they came from, into one.
+///
+/// Each audit is tagged with the source it came from, unless it was already
+/// aggregated from somewhere else. Audits that are identical apart from that
+/// are only kept once (from the first source that has them). The criteria
+/// tables are merged too, and any criteria that the sources define
+/// differently are reported together.
+pub fn merge_audits(
+    sources: Vec<(String, AuditsFile)>,
+) -> Result<AuditsFile, CriteriaConflictErrors> {
+    let mut criteria = SortedMap::<_, (String, CriteriaEntry)>::new();
+    let mut audits = SortedMap::<_, Vec<AuditEntry>>::new();
+    let mut conflicts = vec![];
+    for (url, source) in sources {
+        for (criteria_name, entry) in source.criteria {
+            match criteria.get(&criteria_name) {
+                None => {
+                    criteria.insert(criteria_name, (url.clone(), entry));
+                }
+                Some((first_url, first)) => {
+                    if *first != entry {
+                        conflicts.push(CriteriaConflict {
+                            criteria_name,
+                            first_url: first_url.clone(),
+                            first: first.clone(),
+                            second_url: url.clone(),
+                            second: entry,
+                        });
+                    }
+                }
+            }
+        }
+
+        for (package, entries) in source.audits {
+            let merged = audits.entry(package).or_default();
+            for mut entry in entries {
+                let duplicate = merged.iter().any(|existing| {
+                    AuditEntry {
+                        aggregated_from: None,
+                        ..existing.clone()
+                    } == AuditEntry {
+                        aggregated_from: None,
+                        ..entry.clone()
+                    }
+                });
+                if !duplicate {
+                    entry.aggregated_from.get_or_insert_with(|| url.clone());
+                    merged.push(entry);
+                }
+            }
+        }
+    }
+
+    if !conflicts.is_empty() {
+        return Err(CriteriaConflictErrors { conflicts });
+    }
+    Ok(AuditsFile {
+        criteria: criteria
+            .into_iter()
+            .map(|(name, (_, entry))| (name, entry))
+            .collect(),
+        audits,
+    })
+}
+
+/// Write out an audits.toml that isn't part of a store, like the output of
+/// `cargo vet aggregate`.
+pub fn write_audits(writer: impl Write, audits: AuditsFile) -> Result<(), VetError> {
+    store_audits(writer, audits, None)
+}
+
+/// Find all the foreign criteria that `config` maps to our own criteria whose
+/// definitions differ between `old` and `new`. This includes the criteria they
+/// imply, as those are part of what the mapped criteria mean.
+///
+/// Imports that we didn't have before (and so haven't seen any definitions
+/// for yet) never have changes.
+fn criteria_changes(
+    config: &ConfigFile,
+    old: &ImportsFile,
+    new: &ImportsFile,
+) -> Vec<CriteriaChange> {
+    let mut changes = vec![];
+    for (import_name, import) in &config.imports {
+        let (old_audits, new_audits) =
+            match (old.audits.get(import_name), new.audits.get(import_name)) {
+                (Some(old_audits), Some(new_audits)) => (old_audits, new_audits),
+                _ => continue,
+            };
+
+        let mut visited = SortedSet::new();
+        let mut queue: Vec<&str> = import
+            .criteria_map
+            .iter()
+            .flat_map(|mapping| &mapping.theirs)
+            .map(|criteria| &**criteria)
+            .collect();
+        while let Some(criteria) = queue.pop() {
+            if !visited.insert(criteria) {
+                continue;
+            }
+            let old_entry = old_audits.criteria.get(criteria);
+            let new_entry = new_audits.criteria.get(criteria);
+            for entry in old_entry.iter().chain(new_entry.iter()) {
+                queue.extend(entry.implies.iter().map(|implied| &**implied));
+            }
+            if old_entry != new_entry {
+                changes.push(CriteriaChange {
+                    import_name: import_name.clone(),
+                    criteria_name: criteria.to_owned(),
+                    old: old_entry.cloned(),
+                    new: new_entry.cloned(),
+                });
+            }
+        }
+    }
+    changes.sort_by(|a, b| {
+        (&a.import_name, &a.criteria_name).cmp(&(&b.import_name, &b.criteria_name))
+    });
+    changes
+}
+
+/// The results of fetching all of our imports.
+pub(crate) struct FetchedImports {
+    /// The imports we managed to fetch
+    pub imports: ImportsFile,
+    /// The sha256 of each import we managed to fetch
+    pub hashes: SortedMap<ImportName, String>,
+    pub errors: Vec<ImportFetchError>,
+}
+
+/// Why we couldn't fetch an import.
+#[derive(Debug)]
+pub enum ImportFetchFailure {
+    /// We couldn't download it
+    Load(String),
+    /// We downloaded it, but it isn't what the import is pinned to
+    Mismatch(String),
+    /// We downloaded it, but it isn't a valid audits.toml
+    Parse(String),
+}
+
+/// An import that we couldn't fetch.
+#[derive(Debug)]
+pub struct ImportFetchError {
+    pub import_name: ImportName,
+    pub url: String,
+    pub failure: ImportFetchFailure,
+}
+
+impl ImportFetchFailure {
+    /// What we were doing when this happened, and what went wrong
+    fn describe(&self) -> (&'static str, &str) {
+        match self {
+            ImportFetchFailure::Load(message) => ("load", message),
+            ImportFetchFailure::Mismatch(message) => ("verify", message),
+            ImportFetchFailure::Parse(message) => ("parse", message),
+        }
+    }
+}
+
+impl fmt::Display for ImportFetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (action, message) = self.failure.describe();
+        write!(
+            f,
+            "could not {action} {} @ {} - {message}",
+            self.import_name, self.url
+        )
+    }
+}
+
+/// All the imports that we couldn't fetch.
+#[derive(Debug)]
+pub struct ImportFetchErrors {
+    pub errors: Vec<ImportFetchError>,
+}
+
+impl fmt::Display for ImportFetchErrors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "failed to fetch {} imports", self.errors.len())?;
+        for error in &self.errors {
+            for line in error.to_string().lines() {
+                write!(f, "\n  {line}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ImportFetchErrors {}
+
+/// A foreign criteria that one of our `criteria-map`s relies on, whose
+/// definition changed since we last fetched its import.
+#[derive(Debug)]
+pub struct CriteriaChange {
+    pub import_name: ImportName,
+    pub criteria_name: ForeignCriteriaName,
+    /// The definition we had accepted, if it was defined
+    pub old: Option<CriteriaEntry>,
+    /// The definition they have now, if it's still defined
+    pub new: Option<CriteriaEntry>,
+}
+
+/// The definition of a criteria, one field per line, for showing how two
+/// definitions differ.
+fn criteria_entry_lines(entry: Option<&CriteriaEntry>) -> Vec<String> {
+    let entry = match entry {
+        Some(entry) => entry,
+        None => return vec!["(not defined)".to_owned()],
+    };
+    let mut lines = vec![];
+    if let Some(description) = &entry.description {
+        lines.extend(
+            description
+                .lines()
+                .map(|line| format!("description: {line}")),
+        );
+    }
+    if let Some(url) = &entry.description_url {
+        lines.push(format!("description-url: {url}"));
+    }
+    if !entry.implies.is_empty() {
+        lines.push(format!("implies: {}", entry.implies.join(", ")));
+    }
+    lines
+}
+
+impl fmt::Display for CriteriaChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "`{}` changed the definition of `{}`:",
+            self.import_name, self.criteria_name
+        )?;
+        let old = criteria_entry_lines(self.old.as_ref());
+        let new = criteria_entry_lines(self.new.as_ref());
+        for line in &old {
+            if !new.contains(line) {
+                write!(f, "\n  - {line}")?;
+            }
+        }
+        for line in &new {
+            if old.contains(line) {
+                write!(f, "\n    {line}")?;
+            } else {
+                write!(f, "\n  + {line}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The error for foreign criteria changing under us.
+#[derive(Debug)]
+pub struct CriteriaChangeErrors {
+    pub changes: Vec<CriteriaChange>,
+}
+
+impl fmt::Display for CriteriaChangeErrors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "imported criteria changed their definitions ({} changes)",
+            self.changes.len()
+        )?;
+        for change in &self.changes {
+            for line in change.to_string().lines() {
+                write!(f, "\n  {line}")?;
+            }
+        }
+        write!(
+            f,
+            "\nrun `cargo vet accept-criteria-change` once you've checked these still mean what you expect"
+        )
+    }
+}
+
+impl std::error::Error for CriteriaChangeErrors {}
+
+/// A source for `cargo vet aggregate` that we couldn't fetch.
+#[derive(Debug)]
+pub struct AggregateSourceError {
+    pub url: String,
+    pub failure: ImportFetchFailure,
+}
+
+impl fmt::Display for AggregateSourceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (action, message) = self.failure.describe();
+        write!(f, "could not {action} {} - {message}", self.url)
+    }
+}
+
+/// All the sources for `cargo vet aggregate` that we couldn't fetch.
+#[derive(Debug)]
+pub struct AggregateSourceErrors {
+    pub errors: Vec<AggregateSourceError>,
+}
+
+impl fmt::Display for AggregateSourceErrors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "failed to fetch {} sources", self.errors.len())?;
+        for error in &self.errors {
+            for line in error.to_string().lines() {
+                write!(f, "\n  {line}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for AggregateSourceErrors {}
+
+/// A criteria that two of the sources being aggregated define differently.
+#[derive(Debug)]
+pub struct CriteriaConflict {
+    pub criteria_name: CriteriaName,
+    pub first_url: String,
+    pub first: CriteriaEntry,
+    pub second_url: String,
+    pub second: CriteriaEntry,
+}
+
+impl fmt::Display for CriteriaConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "`{}` is defined differently by:", self.criteria_name)?;
+        for (url, entry) in [
+            (&self.first_url, &self.first),
+            (&self.second_url, &self.second),
+        ] {
+            write!(f, "\n  {url}")?;
+            for line in criteria_entry_lines(Some(entry)) {
+                write!(f, "\n    {line}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The error for the sources being aggregated disagreeing about criteria.
+#[derive(Debug)]
+pub struct CriteriaConflictErrors {
+    pub conflicts: Vec<CriteriaConflict>,
+}
+
+impl fmt::Display for CriteriaConflictErrors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "sources have conflicting criteria definitions ({} conflicts)",
+            self.conflicts.len()
+        )?;
+        for conflict in &self.conflicts {
+            for line in conflict.to_string().lines() {
+                write!(f, "\n  {line}")?;
+            }
+        }
+        write!(
+            f,
+            "\nrename the criteria in one of the sources so they can be told apart"
+        )
+    }
+}
+
+impl std::error::Error for CriteriaConflictErrors {}
+
+/// A problem found by [`Store::validate`][].
+#[derive(Debug)]
+pub struct StoreValidateError {
+    /// The file in the store that has the problem
+    pub file: &'static str,
+    pub message: String,
+    /// Where in the file the problem is, if we could work it out
+    pub location: Option<SourceLocation>,
+    // The path to the offending value in the file, used to find `location`
+    path: TomlPath,
+}
+
+impl StoreValidateError {
+    fn new(file: &'static str, path: TomlPath, message: String) -> Self {
+        Self {
+            file,
+            message,
+            location: None,
+            path,
+        }
+    }
+}
+
+impl fmt::Display for StoreValidateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(
+                f,
+                "{}:{}:{}: {}\n{}",
+                self.file, location.line, location.column, self.message, location
+            ),
+            None => write!(f, "{}: {}", self.file, self.message),
+        }
+    }
+}
+
+/// All the problems found by [`Store::validate`][].
+#[derive(Debug)]
+pub struct StoreValidateErrors {
+    pub errors: Vec<StoreValidateError>,
+}
+
+impl fmt::Display for StoreValidateErrors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the store is invalid ({} errors)", self.errors.len())?;
+        for error in &self.errors {
+            for line in error.to_string().lines() {
+                write!(f, "\n  {line}")?;
+            }
+        }
         Ok(())
     }
 }
 
+impl std::error::Error for StoreValidateErrors {}
+
+/// A file in the store (or one of our caches) that isn't valid TOML, or
+/// doesn't have the structure we expect.
+#[derive(Debug)]
+pub struct StoreParseError {
+    pub file: String,
+    pub message: String,
+    pub location: Option<SourceLocation>,
+}
+
+impl fmt::Display for StoreParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(
+                f,
+                "failed to parse {}:{}:{}: {}\n{}",
+                self.file, location.line, location.column, self.message, location
+            ),
+            None => write!(f, "failed to parse {}: {}", self.file, self.message),
+        }
+    }
+}
+
+impl std::error::Error for StoreParseError {}
+
+/// A spot in a file that a diagnostic is pointing at
+#[derive(Debug, Clone)]
+pub struct SourceLocation {
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column number (in chars)
+    pub column: usize,
+    /// The full text of the line
+    pub snippet: String,
+    // How many chars of the snippet (starting at `column`) to underline
+    width: usize,
+}
+
+impl SourceLocation {
+    /// Describe where the given byte range of `src` is.
+    ///
+    /// Spans covering several lines are only underlined up to the end of
+    /// their first line.
+    fn from_span(src: &str, span: Range<usize>) -> Self {
+        let mut start = span.start.min(src.len());
+        while !src.is_char_boundary(start) {
+            start -= 1;
+        }
+        let line_start = src[..start].rfind('\n').map_or(0, |idx| idx + 1);
+        let line_end = src[start..].find('\n').map_or(src.len(), |idx| start + idx);
+        let snippet = src[line_start..line_end].trim_end_matches('\r');
+        let column = src[line_start..start].chars().count();
+        let width = snippet
+            .chars()
+            .skip(column)
+            .take(span.end.saturating_sub(start))
+            .count();
+        Self {
+            line: src[..line_start].matches('\n').count() + 1,
+            column: column + 1,
+            snippet: snippet.to_owned(),
+            width: width.max(1),
+        }
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let line = self.line.to_string();
+        let gutter = " ".repeat(line.len());
+        let indent = " ".repeat(self.column - 1);
+        let underline = "^".repeat(self.width);
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{line} | {}", self.snippet)?;
+        write!(f, "{gutter} | {indent}{underline}")
+    }
+}
+
+/// The path to a value in a TOML document, so diagnostics can point at it.
+type TomlPath = Vec<TomlPathSegment>;
+
+#[derive(Debug, Clone)]
+enum TomlPathSegment {
+    Key(String),
+    Index(usize),
+}
+
+impl From<&str> for TomlPathSegment {
+    fn from(key: &str) -> Self {
+        Self::Key(key.to_owned())
+    }
+}
+
+impl From<&String> for TomlPathSegment {
+    fn from(key: &String) -> Self {
+        Self::Key(key.clone())
+    }
+}
+
+impl From<usize> for TomlPathSegment {
+    fn from(idx: usize) -> Self {
+        Self::Index(idx)
+    }
+}
+
+fn join_path(base: &[TomlPathSegment], rest: TomlPath) -> TomlPath {
+    base.iter().cloned().chain(rest).collect()
+}
+
+/// Find the span of the value at `path` in a parsed TOML document.
+///
+/// This is best-effort: if the path can't be followed all the way, we point
+/// at the deepest thing we did find.
+fn find_span(doc: &toml_edit::ImDocument<&str>, path: &[TomlPathSegment]) -> Option<Range<usize>> {
+    let mut item = doc.as_item();
+    let mut span = None;
+    for segment in path {
+        let child = match segment {
+            TomlPathSegment::Key(key) => {
+                match item
+                    .as_table_like()
+                    .and_then(|table| table.get_key_value(key))
+                {
+                    Some((key, child)) => {
+                        // Implicit tables (`[a.b]` defines `a`) have no span of their own
+                        span = child.span().or_else(|| key.span()).or(span);
+                        child
+                    }
+                    None => break,
+                }
+            }
+            TomlPathSegment::Index(idx) => match item.get(idx) {
+                Some(child) => {
+                    span = child.span().or(span);
+                    child
+                }
+                // Lists of criteria can also be written as a single string
+                None if !item.is_array() && !item.is_array_of_tables() => continue,
+                None => break,
+            },
+        };
+        item = child;
+    }
+    span
+}
+
+/// All the criteria names that can be used by entries of this file.
+fn valid_criteria(audits: &AuditsFile) -> FastSet<CriteriaStr<'_>> {
+    audits
+        .criteria
+        .keys()
+        .map(|name| &**name)
+        .chain([SAFE_TO_RUN, SAFE_TO_DEPLOY])
+        .collect()
+}
+
+fn check_criteria(
+    errors: &mut Vec<StoreValidateError>,
+    file: &'static str,
+    path: TomlPath,
+    valid: &FastSet<CriteriaStr>,
+    criteria: CriteriaStr,
+    context: impl FnOnce() -> String,
+) {
+    if !valid.contains(criteria) {
+        errors.push(StoreValidateError::new(
+            file,
+            path,
+            format!("{} uses unknown criteria `{criteria}`", context()),
+        ));
+    }
+}
+
+/// Check that the `key` list of target triples at `base` in config.toml
+/// doesn't contain anything else.
+fn check_target_triples(
+    errors: &mut Vec<StoreValidateError>,
+    base: &[TomlPathSegment],
+    key: &'static str,
+    triples: Option<&[String]>,
+    context: impl Fn() -> String,
+) {
+    for (idx, target) in triples.into_iter().flatten().enumerate() {
+        let message = match targets::parse_target_spec(target) {
+            Ok(Platform::Name(_)) => match targets::check_target_triple(target) {
+                Ok(()) => continue,
+                Err(e) => format!("{} has an unknown target `{target}`: {e}", context()),
+            },
+            Ok(Platform::Cfg(_)) => {
+                format!("{} should list target triples, not `{target}`", context())
+            }
+            Err(e) => format!("{} has an invalid target `{target}`: {e}", context()),
+        };
+        errors.push(StoreValidateError::new(
+            CONFIG_TOML,
+            join_path(base, toml_path![key, idx]),
+            message,
+        ));
+    }
+}
+
+/// Validate the criteria and audits of an audits.toml (our own, or an imported one).
+///
+/// `base` is the path to the audits file's contents within `file`.
+///
+/// Problems that don't stop us from understanding the file (like a criteria
+/// without a description) go in `lints` instead of `errors`.
+fn validate_audits_file(
+    errors: &mut Vec<StoreValidateError>,
+    lints: &mut Vec<StoreValidateError>,
+    file: &'static str,
+    prefix: &str,
+    base: &[TomlPathSegment],
+    audits: &AuditsFile,
+    valid: &FastSet<CriteriaStr>,
+) {
+    for (name, entry) in &audits.criteria {
+        let criteria_path = join_path(base, toml_path!["criteria", name]);
+        if name == SAFE_TO_RUN || name == SAFE_TO_DEPLOY {
+            lints.push(StoreValidateError::new(
+                file,
+                criteria_path.clone(),
+                format!("{prefix}criteria `{name}` shadows a builtin criteria"),
+            ));
+        }
+        if entry.description.is_none() && entry.description_url.is_none() {
+            lints.push(StoreValidateError::new(
+                file,
+                criteria_path.clone(),
+                format!("{prefix}criteria `{name}` needs a `description` or `description-url`"),
+            ));
+        }
+        for (idx, implied) in entry.implies.iter().enumerate() {
+            check_criteria(
+                errors,
+                file,
+                join_path(&criteria_path, toml_path!["implies", idx]),
+                valid,
+                implied,
+                || format!("{prefix}criteria `{name}` (implies)"),
+            );
+        }
+
+        // An implies cycle would make the criteria mapper recurse forever
+        let mut visited = SortedSet::new();
+        let mut stack = entry.implies.iter().collect::<Vec<_>>();
+        while let Some(implied) = stack.pop() {
+            if implied == name {
+                errors.push(StoreValidateError::new(
+                    file,
+                    join_path(&criteria_path, toml_path!["implies"]),
+                    format!("{prefix}criteria `{name}` implies itself"),
+                ));
+                break;
+            }
+            if visited.insert(implied) {
+                if let Some(implied_entry) = audits.criteria.get(implied) {
+                    stack.extend(&implied_entry.implies);
+                }
+            }
+        }
+    }
+
+    for (package, entries) in &audits.audits {
+        for (entry_idx, entry) in entries.iter().enumerate() {
+            let entry_path = join_path(base, toml_path!["audits", package, entry_idx]);
+            let (what, dependency_criteria) = match &entry.kind {
+                AuditKind::Full {
+                    version,
+                    dependency_criteria,
+                } => (
+                    format!("audit of `{package}:{version}`"),
+                    Some(dependency_criteria),
+                ),
+                AuditKind::Delta {
+                    delta,
+                    dependency_criteria,
+                } => {
+                    if delta.from == delta.to {
+                        lints.push(StoreValidateError::new(
+                            file,
+                            join_path(&entry_path, toml_path!["delta"]),
+                            format!(
+                                "{prefix}audit of `{package}` is a no-op delta (`{} -> {}`)",
+                                delta.from, delta.to
+                            ),
+                        ));
+                    }
+                    (
+                        format!("audit of `{package}:{} -> {}`", delta.from, delta.to),
+                        Some(dependency_criteria),
+                    )
+                }
+                AuditKind::Violation { violation } => {
+                    (format!("violation of `{package}:{violation}`"), None)
+                }
+            };
+            check_criteria(
+                errors,
+                file,
+                join_path(&entry_path, toml_path!["criteria"]),
+                valid,
+                &entry.criteria,
+                || format!("{prefix}{what}"),
+            );
+            for (idx, target) in entry.targets.iter().flatten().enumerate() {
+                if let Err(e) = targets::parse_target_spec(target) {
+                    errors.push(StoreValidateError::new(
+                        file,
+                        join_path(&entry_path, toml_path!["targets", idx]),
+                        format!("{prefix}{what} has an invalid target `{target}`: {e}"),
+                    ));
+                }
+            }
+            for (dep, criteria) in dependency_criteria.into_iter().flatten() {
+                for (idx, criteria) in criteria.iter().enumerate() {
+                    check_criteria(
+                        errors,
+                        file,
+                        join_path(&entry_path, toml_path!["dependency-criteria", dep, idx]),
+                        valid,
+                        criteria,
+                        || format!("{prefix}{what} (dependency-criteria for `{dep}`)"),
+                    );
+                }
+            }
+        }
+    }
+}
+
 /// A Registry in CARGO_HOME (usually the crates.io one)
 pub struct CargoRegistry {
     /// The base path all registries share
@@ -354,7 +2086,8 @@ impl Cache {
             .unwrap_or_else(|| root.join(TEMP_DIFF_CACHE));
         let diff_cache: DiffCache = File::open(&diff_cache_path)
             .ok()
-            .and_then(|f| load_toml(f).ok())
+            .and_then(|f| load_toml(TEMP_DIFF_CACHE, f).ok())
+            .map(|(diff_cache, _)| diff_cache)
             .unwrap_or_default();
 
         // Setup the command_history.
@@ -487,7 +2220,7 @@ impl Cache {
                     let diffstat = crate::diffstat_crate(from, to)?;
                     self.diff_cache
                         .entry(package.to_string())
-                        .or_insert(SortedMap::new())
+                        .or_default()
                         .insert(delta.clone(), diffstat.clone());
                     diffstat
                 } else {
@@ -589,6 +2322,7 @@ impl Cache {
         // which may have been extracted from the package.
         let mut ok = OpenOptions::new()
             .create(true)
+            .truncate(true)
             .read(true)
             .write(true)
             .open(&lockfile)
@@ -664,25 +2398,155 @@ fn find_cargo_registry(cfg: &PartialConfig) -> Result<CargoRegistry, VetError> {
     }
 }
 
-fn load_toml<T>(reader: impl Read) -> Result<T, VetError>
+fn load_toml<T>(file: &str, reader: impl Read) -> Result<(T, String), VetError>
+where
+    T: for<'a> Deserialize<'a>,
+{
+    let mut reader = BufReader::new(reader);
+    let mut string = String::new();
+    reader.read_to_string(&mut string)?;
+    let toml = parse_toml(file, &string)?;
+    Ok((toml, string))
+}
+fn parse_toml<T>(file: &str, src: &str) -> Result<T, StoreParseError>
+where
+    T: for<'a> Deserialize<'a>,
+{
+    toml::from_str(src).map_err(|error| {
+        let location = error.line_col().map(|(line, col)| {
+            // toml gives us 0-based (line, byte column), so find the byte offset
+            let line_start = src
+                .split_inclusive('\n')
+                .take(line)
+                .map(str::len)
+                .sum::<usize>();
+            SourceLocation::from_span(src, line_start + col..line_start + col + 1)
+        });
+        // We're going to print the location ourselves, so drop toml's
+        let mut message = error.to_string();
+        if let Some(idx) = message.rfind(" at line ") {
+            if location.is_some() {
+                message.truncate(idx);
+            }
+        }
+        StoreParseError {
+            file: file.to_owned(),
+            message,
+            location,
+        }
+    })
+}
+/// Load one of the store's files.
+///
+/// Returns the parsed file, its text, and the format version it's in. Anything
+/// that has to change to upgrade it is done by `migrate_config`.
+fn load_store_toml<T>(file: &str, reader: impl Read) -> Result<(T, String, u64), VetError>
 where
     T: for<'a> Deserialize<'a>,
 {
     let mut reader = BufReader::new(reader);
     let mut string = String::new();
     reader.read_to_string(&mut string)?;
-    let toml = toml::from_str(&string)?;
-    Ok(toml)
+    parse_store_toml(file, string)
+}
+fn parse_store_toml<T>(file: &str, src: String) -> Result<(T, String, u64), VetError>
+where
+    T: for<'a> Deserialize<'a>,
+{
+    #[derive(Deserialize)]
+    struct Versioned {
+        #[serde(rename = "format-version")]
+        format_version: Option<u64>,
+    }
+
+    let version = parse_toml::<Versioned>(file, &src)?
+        .format_version
+        .unwrap_or(1);
+    if version > FORMAT_VERSION {
+        return Err(eyre::eyre!(
+            "{file} is in format version {version}, but this version of cargo-vet only understands up to version {FORMAT_VERSION} (you may need to update cargo-vet)"
+        ));
+    }
+    let toml = parse_toml(file, &src)?;
+    Ok((toml, src, version))
+}
+
+/// Upgrade config.toml, with text `src`, from `version` to the current format
+/// version. The other files haven't changed in ways that need upgrading.
+///
+/// This is applied to the parsed file rather than the text, so that
+/// diagnostics still point at the right lines, and the changes are written
+/// out to the text when the store is next committed.
+fn migrate_config(config: &mut ConfigFile, src: &str, version: u64) {
+    if version < 2 {
+        // Version 2 made imports map the built-in criteria by default, so
+        // existing imports have to opt out to keep trusting what they did.
+        // (It also added `targets` to audits, and scoping to imports, but
+        // leaving those out means what it always did.)
+        let doc = toml_edit::ImDocument::parse(src).ok();
+        let imports = doc
+            .as_ref()
+            .and_then(|doc| doc.get("imports"))
+            .and_then(|imports| imports.as_table_like());
+        for (name, import) in &mut config.imports {
+            let explicit = imports
+                .and_then(|imports| imports.get(name))
+                .and_then(|import| import.as_table_like())
+                .is_some_and(|import| import.contains_key("map-builtins"));
+            if !explicit {
+                import.map_builtins = false;
+            }
+        }
+    }
 }
+
 fn store_toml<T>(mut writer: impl Write, heading: &str, val: T) -> Result<(), VetError>
 where
     T: Serialize,
 {
-    // FIXME: do this in a temp file and swap it into place to avoid corruption?
+    // NOTE: the store's files are written to a buffer first and swapped into
+    // place by `Store::commit`, so only caches are written directly.
     let toml_string = toml::to_string(&val)?;
     writeln!(writer, "{}\n{}", heading, toml_string)?;
     Ok(())
 }
+/// What to do with the existing layout of a hand-editable file when rewriting it
+#[derive(Clone, Copy)]
+enum Layout {
+    /// Only touch the entries that changed
+    Preserve,
+    /// Put everything back in the usual layout, keeping only the comments
+    Reformat,
+}
+
+/// Like `store_toml`, but for the store's files.
+///
+/// These are stamped with the current format version. If we have the
+/// `original` text of the file, it's updated to match `val` instead of being
+/// overwritten, according to the given [`Layout`][].
+fn store_versioned_toml<T>(
+    mut writer: impl Write,
+    heading: &str,
+    val: T,
+    original: Option<(&str, Layout)>,
+) -> Result<(), VetError>
+where
+    T: Serialize,
+{
+    let toml_string = toml::to_string(&val)?;
+    let version = format!("format-version = {}\n", FORMAT_VERSION);
+    let fresh = || format!("{}{}\n{}\n", version, heading, toml_string);
+    let output = match original {
+        None => fresh(),
+        // Leave out the heading, which the original already has (or doesn't want)
+        Some((original, Layout::Preserve)) => {
+            editor::update_in_place(original, &format!("{}{}", version, toml_string))?
+        }
+        Some((original, Layout::Reformat)) => editor::reformat(original, &fresh())?,
+    };
+    write!(writer, "{}", output)?;
+    Ok(())
+}
 fn load_json<T>(reader: impl Read) -> Result<T, VetError>
 where
     T: for<'a> Deserialize<'a>,
@@ -703,7 +2567,11 @@ where
     Ok(())
 }
 
-fn store_audits(writer: impl Write, mut audits: AuditsFile) -> Result<(), VetError> {
+fn store_audits(
+    writer: impl Write,
+    mut audits: AuditsFile,
+    original: Option<(&str, Layout)>,
+) -> Result<(), VetError> {
     let heading = r###"
 # cargo-vet audits file
 "###;
@@ -712,10 +2580,14 @@ fn store_audits(writer: impl Write, mut audits: AuditsFile) -> Result<(), VetErr
         .values_mut()
         .for_each(|entries| entries.sort());
 
-    store_toml(writer, heading, audits)?;
+    store_versioned_toml(writer, heading, audits, original)?;
     Ok(())
 }
-fn store_config(writer: impl Write, mut config: ConfigFile) -> Result<(), VetError> {
+fn store_config(
+    writer: impl Write,
+    mut config: ConfigFile,
+    original: Option<(&str, Layout)>,
+) -> Result<(), VetError> {
     config
         .unaudited
         .values_mut()
@@ -725,7 +2597,7 @@ fn store_config(writer: impl Write, mut config: ConfigFile) -> Result<(), VetErr
 # cargo-vet config file
 "###;
 
-    store_toml(writer, heading, config)?;
+    store_versioned_toml(writer, heading, config, original)?;
     Ok(())
 }
 fn store_imports(writer: impl Write, imports: ImportsFile) -> Result<(), VetError> {
@@ -733,7 +2605,7 @@ fn store_imports(writer: impl Write, imports: ImportsFile) -> Result<(), VetErro
 # cargo-vet imports lock
 "###;
 
-    store_toml(writer, heading, imports)?;
+    store_versioned_toml(writer, heading, imports, None)?;
     Ok(())
 }
 fn store_diff_cache(writer: impl Write, diff_cache: DiffCache) -> Result<(), VetError> {
@@ -0,0 +1,200 @@
+//! The platforms a project is built for, and whether the `targets` of an
+//! audit entry (written with Cargo's platform-specific dependency syntax)
+//! cover them.
+
+use std::{
+    ffi::OsString,
+    process::Command,
+    str::FromStr,
+    sync::{Mutex, OnceLock},
+};
+
+use cargo_platform::{Cfg, Platform};
+use eyre::eyre;
+
+use crate::{format::FastMap, VetError};
+
+/// A target triple we build for, along with the cfgs rustc sets for it.
+#[derive(Debug, Clone)]
+pub struct Target {
+    pub triple: String,
+    pub cfg: Vec<Cfg>,
+}
+
+/// The targets we build for. `None` means we consider every platform.
+#[derive(Debug, Clone, Default)]
+pub struct TargetSet {
+    targets: Option<Vec<Target>>,
+}
+
+impl TargetSet {
+    /// The given target triples (or every platform if `None`).
+    ///
+    /// The cfgs of each triple are looked up with `rustc --print cfg`, which
+    /// fails if rustc doesn't know the target. Store validation reports that
+    /// more helpfully (see [`check_target_triple`][]), but it can still happen
+    /// here if rustc goes missing or the store wasn't validated.
+    pub fn new(triples: Option<&[String]>) -> Result<Self, VetError> {
+        let targets = triples
+            .map(|triples| {
+                triples
+                    .iter()
+                    .map(|triple| {
+                        let cfg = target_cfg(triple).map_err(|e| {
+                            eyre!("couldn't get the cfgs for the target `{triple}`: {e}")
+                        })?;
+                        Ok(Target {
+                            triple: triple.clone(),
+                            cfg,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, VetError>>()
+            })
+            .transpose()?;
+        Ok(Self { targets })
+    }
+
+    /// Every platform.
+    pub const fn all() -> Self {
+        Self { targets: None }
+    }
+
+    /// No targets at all.
+    pub fn none() -> Self {
+        Self {
+            targets: Some(Vec::new()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        matches!(&self.targets, Some(targets) if targets.is_empty())
+    }
+
+    /// Whether a dependency restricted to `platform` (`None` for no
+    /// restriction) is active on any target in this set.
+    pub fn matches(&self, platform: Option<&Platform>) -> bool {
+        match (&self.targets, platform) {
+            (Some(targets), Some(platform)) => targets
+                .iter()
+                .any(|target| platform.matches(&target.triple, &target.cfg)),
+            (Some(targets), None) => !targets.is_empty(),
+            (None, _) => true,
+        }
+    }
+
+    /// The targets in this set that a dependency restricted to `platform`
+    /// (`None` for no restriction) is active on.
+    pub fn restricted_to(&self, platform: Option<&Platform>) -> Self {
+        match (&self.targets, platform) {
+            (Some(targets), Some(platform)) => Self {
+                targets: Some(
+                    targets
+                        .iter()
+                        .filter(|target| platform.matches(&target.triple, &target.cfg))
+                        .cloned()
+                        .collect(),
+                ),
+            },
+            _ => self.clone(),
+        }
+    }
+
+    /// Add the targets in `other` to this set, returning whether it grew.
+    pub fn union_with(&mut self, other: &Self) -> bool {
+        match (&mut self.targets, &other.targets) {
+            (None, _) => false,
+            (targets @ Some(_), None) => {
+                *targets = None;
+                true
+            }
+            (Some(targets), Some(others)) => {
+                let mut grew = false;
+                for other in others {
+                    if !targets.iter().any(|target| target.triple == other.triple) {
+                        targets.push(other.clone());
+                        grew = true;
+                    }
+                }
+                grew
+            }
+        }
+    }
+
+    /// Whether an entry restricted to `specs` (`None` for no restriction)
+    /// covers every target in this set.
+    pub fn covered_by(&self, specs: Option<&[String]>) -> bool {
+        let specs = match specs {
+            Some(specs) => specs,
+            None => return true,
+        };
+        match &self.targets {
+            Some(targets) => targets
+                .iter()
+                .all(|target| specs.iter().any(|spec| spec_matches(spec, target))),
+            None => false,
+        }
+    }
+
+    /// Whether an entry restricted to `specs` (`None` for no restriction)
+    /// concerns any target in this set.
+    pub fn touched_by(&self, specs: Option<&[String]>) -> bool {
+        let specs = match specs {
+            Some(specs) => specs,
+            None => return true,
+        };
+        match &self.targets {
+            Some(targets) => targets
+                .iter()
+                .any(|target| specs.iter().any(|spec| spec_matches(spec, target))),
+            None => true,
+        }
+    }
+}
+
+/// Whether a target spec (a triple or a `cfg(..)` expression) matches `target`.
+/// Specs that don't parse never match; validation reports those.
+fn spec_matches(spec: &str, target: &Target) -> bool {
+    match Platform::from_str(spec) {
+        Ok(platform) => platform.matches(&target.triple, &target.cfg),
+        Err(_) => false,
+    }
+}
+
+/// Check that a target spec is a triple or a `cfg(..)` expression that Cargo
+/// would accept.
+pub fn parse_target_spec(spec: &str) -> Result<Platform, VetError> {
+    Platform::from_str(spec).map_err(|e| eyre!(e))
+}
+
+/// Check that rustc knows about the target `triple`.
+pub fn check_target_triple(triple: &str) -> Result<(), VetError> {
+    target_cfg(triple).map(drop)
+}
+
+/// The cfgs rustc sets for `triple`. Results are cached, since we resolve
+/// several times in one run.
+fn target_cfg(triple: &str) -> Result<Vec<Cfg>, VetError> {
+    static CACHE: OnceLock<Mutex<FastMap<String, Vec<Cfg>>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(Default::default);
+    if let Some(cfg) = cache.lock().unwrap().get(triple) {
+        return Ok(cfg.clone());
+    }
+
+    let rustc = std::env::var_os("RUSTC").unwrap_or_else(|| OsString::from("rustc"));
+    let out = Command::new(rustc)
+        .args(["--print", "cfg", "--target", triple])
+        .output()?;
+    if !out.status.success() {
+        return Err(eyre!(
+            "rustc failed: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        ));
+    }
+    let cfg = String::from_utf8(out.stdout)?
+        .lines()
+        .map(|line| Cfg::from_str(line).map_err(|e| eyre!(e)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    cache.lock().unwrap().insert(triple.to_owned(), cfg.clone());
+    Ok(cfg)
+}
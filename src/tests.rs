@@ -1,23 +1,41 @@
-use std::{collections::BTreeMap, ffi::OsString, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    fs,
+    io::{Read, Write},
+    net::TcpListener,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
 
 use cargo_metadata::{Metadata, Version};
 use serde_json::{json, Value};
 
 use crate::{
     format::{
-        AuditKind, CriteriaName, CriteriaStr, Delta, DependencyCriteria, MetaConfig, PackageName,
-        PackageStr, PolicyEntry, VersionReq, SAFE_TO_DEPLOY, SAFE_TO_RUN,
+        AcknowledgedViolation, AuditKind, CriteriaMapping, CriteriaName, CriteriaStr, Delta,
+        DependencyCriteria, MetaConfig, MetaConfigInstance, PackageName, PackageStr, PolicyEntry,
+        StoreInfo, VersionReq, SAFE_TO_DEPLOY, SAFE_TO_RUN,
     },
     init_files,
-    resolver::ResolveReport,
+    resolver::{
+        Conclusion, CriteriaSet, ResolveReport, SearchResult, MAX_FAILED_DEPS_ALTERNATIVES,
+        MAX_WHY_PATHS,
+    },
+    storage::{self, sha256_hex, FetchedImports},
     AuditEntry, AuditsFile, Cli, Config, ConfigFile, CriteriaEntry, ImportsFile, PackageExt,
-    PartialConfig, SortedMap, Store, UnauditedDependency,
+    PartialConfig, RemoteImport, SortedMap, Store, UnauditedDependency,
 };
 
 // Some room above and below
 const DEFAULT_VER: u64 = 10;
 const DEFAULT_CRIT: CriteriaStr = "reviewed";
 
+/// The deps of `parent` in `MockMetadata::many_alternatives`, a pair for each
+/// step of its audit chain.
+const LADDER_DEPS: [[&str; 2]; 4] = [["a1", "b1"], ["a2", "b2"], ["a3", "b3"], ["a4", "b4"]];
+
 struct MockMetadata {
     packages: Vec<MockPackage>,
     pkgids: Vec<String>,
@@ -38,6 +56,8 @@ struct MockPackage {
 struct MockDependency {
     name: &'static str,
     version: Version,
+    /// The platform this dependency is restricted to, if any
+    target: Option<&'static str>,
 }
 
 impl Default for MockPackage {
@@ -65,6 +85,11 @@ fn ver(major: u64) -> Version {
     }
 }
 
+/// A list of target specs, for `targets` and friends.
+fn targets(specs: &[&str]) -> Vec<String> {
+    specs.iter().map(|spec| spec.to_string()).collect()
+}
+
 fn dep(name: &'static str) -> MockDependency {
     dep_ver(name, DEFAULT_VER)
 }
@@ -73,6 +98,14 @@ fn dep_ver(name: &'static str, version: u64) -> MockDependency {
     MockDependency {
         name,
         version: ver(version),
+        target: None,
+    }
+}
+
+fn dep_target(name: &'static str, target: &'static str) -> MockDependency {
+    MockDependency {
+        target: Some(target),
+        ..dep(name)
     }
 }
 
@@ -133,6 +166,8 @@ fn delta_audit(from: Version, to: Version, criteria: CriteriaStr) -> AuditEntry
             delta,
             dependency_criteria: DependencyCriteria::default(),
         },
+        targets: None,
+        aggregated_from: None,
     }
 }
 
@@ -165,6 +200,8 @@ fn delta_audit_dep(
                 })
                 .collect(),
         },
+        targets: None,
+        aggregated_from: None,
     }
 }
 
@@ -177,6 +214,8 @@ fn full_audit(version: Version, criteria: CriteriaStr) -> AuditEntry {
             version,
             dependency_criteria: DependencyCriteria::default(),
         },
+        targets: None,
+        aggregated_from: None,
     }
 }
 
@@ -206,6 +245,8 @@ fn full_audit_dep(
                 })
                 .collect(),
         },
+        targets: None,
+        aggregated_from: None,
     }
 }
 
@@ -215,15 +256,18 @@ fn violation_hard(version: VersionReq) -> AuditEntry {
         notes: None,
         criteria: "weak-reviewed".to_string(),
         kind: AuditKind::Violation { violation: version },
+        targets: None,
+        aggregated_from: None,
     }
 }
-#[allow(dead_code)]
 fn violation(version: VersionReq, criteria: CriteriaStr) -> AuditEntry {
     AuditEntry {
         who: None,
         notes: None,
         criteria: criteria.to_string(),
         kind: AuditKind::Violation { violation: version },
+        targets: None,
+        aggregated_from: None,
     }
 }
 
@@ -267,6 +311,29 @@ fn dep_policy(
     }
 }
 
+/// An audits file with nothing in it.
+fn empty_audits() -> AuditsFile {
+    AuditsFile {
+        criteria: SortedMap::new(),
+        audits: SortedMap::new(),
+    }
+}
+
+/// An import of "https://example.com/audits.toml", trusting its built-in
+/// criteria and nothing else.
+fn remote_import() -> RemoteImport {
+    RemoteImport {
+        url: "https://example.com/audits.toml".to_owned(),
+        criteria_map: vec![],
+        map_builtins: true,
+        include: vec![],
+        exclude: vec![],
+        auditors: vec![],
+        max_criteria: None,
+        sha256: None,
+    }
+}
+
 impl MockMetadata {
     fn simple() -> Self {
         // A simple dependency tree to test basic functionality on.
@@ -436,6 +503,56 @@ impl MockMetadata {
         ])
     }
 
+    fn platform_deps() -> Self {
+        // Dependencies that are only active on some platforms
+        //
+        //                                    Graph
+        // =======================================================================================
+        //
+        //                                      root
+        //                  /             |               \               \
+        //           third-party   (cfg(windows))   (cfg(unix))    (dev, cfg(target_arch = "wasm32"))
+        //                              winapi          libc              wasm-test
+        //                                |
+        //                            winapi-util
+        //
+        MockMetadata::new(vec![
+            MockPackage {
+                name: "root",
+                is_root: true,
+                is_first_party: true,
+                deps: vec![
+                    dep("third-party"),
+                    dep_target("winapi", "cfg(windows)"),
+                    dep_target("libc", "cfg(unix)"),
+                ],
+                dev_deps: vec![dep_target("wasm-test", "cfg(target_arch = \"wasm32\")")],
+                ..Default::default()
+            },
+            MockPackage {
+                name: "third-party",
+                ..Default::default()
+            },
+            MockPackage {
+                name: "winapi",
+                deps: vec![dep("winapi-util")],
+                ..Default::default()
+            },
+            MockPackage {
+                name: "winapi-util",
+                ..Default::default()
+            },
+            MockPackage {
+                name: "libc",
+                ..Default::default()
+            },
+            MockPackage {
+                name: "wasm-test",
+                ..Default::default()
+            },
+        ])
+    }
+
     fn cycle() -> Self {
         // Different dependency cases
         MockMetadata::new(vec![
@@ -527,6 +644,130 @@ impl MockMetadata {
         ])
     }
 
+    fn alternative_blame() -> Self {
+        // A package that could be vetted by fixing either of two sets of its deps
+        //
+        //                  root
+        //                   |
+        //                 parent
+        //          /        |        \
+        //   expensive    cheap1    cheap2
+        //
+        // The mocked diffstat of a full audit grows with the square of the major
+        // version, so fixing `expensive` costs 100 lines, and `cheap1` and `cheap2`
+        // 4 and 9.
+        MockMetadata::new(vec![
+            MockPackage {
+                name: "root",
+                is_root: true,
+                is_first_party: true,
+                deps: vec![dep("parent")],
+                ..Default::default()
+            },
+            MockPackage {
+                name: "parent",
+                deps: vec![dep("expensive"), dep_ver("cheap1", 2), dep_ver("cheap2", 3)],
+                ..Default::default()
+            },
+            MockPackage {
+                name: "expensive",
+                ..Default::default()
+            },
+            MockPackage {
+                name: "cheap1",
+                version: ver(2),
+                ..Default::default()
+            },
+            MockPackage {
+                name: "cheap2",
+                version: ver(3),
+                ..Default::default()
+            },
+        ])
+    }
+
+    fn many_alternatives() -> Self {
+        // A package with a choice of two deps to fix at each step of its audit chain
+        //
+        //              root
+        //               |
+        //             parent
+        //     /    /    |    \    \
+        //   a1   b1    ...    a4   b4
+        MockMetadata::new(vec![
+            MockPackage {
+                name: "root",
+                is_root: true,
+                is_first_party: true,
+                deps: vec![dep("parent")],
+                ..Default::default()
+            },
+            MockPackage {
+                name: "parent",
+                deps: LADDER_DEPS
+                    .iter()
+                    .flatten()
+                    .map(|&name| dep(name))
+                    .collect(),
+                ..Default::default()
+            },
+            MockPackage {
+                name: "a1",
+                ..Default::default()
+            },
+            MockPackage {
+                name: "b1",
+                ..Default::default()
+            },
+            MockPackage {
+                name: "a2",
+                ..Default::default()
+            },
+            MockPackage {
+                name: "b2",
+                ..Default::default()
+            },
+            MockPackage {
+                name: "a3",
+                ..Default::default()
+            },
+            MockPackage {
+                name: "b3",
+                ..Default::default()
+            },
+            MockPackage {
+                name: "a4",
+                ..Default::default()
+            },
+            MockPackage {
+                name: "b4",
+                ..Default::default()
+            },
+        ])
+    }
+
+    fn diverging_chains() -> Self {
+        // Two packages whose audit chains for different criteria go through
+        // different versions
+        MockMetadata::new(vec![
+            MockPackage {
+                name: "root",
+                is_root: true,
+                is_first_party: true,
+                deps: vec![dep("split"), dep("shared")],
+                ..Default::default()
+            },
+            MockPackage {
+                name: "split",
+                ..Default::default()
+            },
+            MockPackage {
+                name: "shared",
+                ..Default::default()
+            },
+        ])
+    }
+
     fn new(packages: Vec<MockPackage>) -> Self {
         let mut pkgids = vec![];
         let mut idx_by_name_and_ver = BTreeMap::<PackageStr, BTreeMap<Version, usize>>::new();
@@ -602,7 +843,7 @@ impl MockMetadata {
                     "optional": false,
                     "uses_default_features": true,
                     "features": [],
-                    "target": null,
+                    "target": dep.target,
                     "registry": null
                 })).collect::<Vec<_>>(),
                 "targets": package.targets.iter().map(|target| json!({
@@ -644,15 +885,15 @@ impl MockMetadata {
             }).collect::<Vec<_>>(),
             "resolve": {
                 "nodes": self.packages.iter().map(|package| {
-                    let mut all_deps = BTreeMap::<(PackageStr, &Version), Vec<Option<&str>>>::new();
+                    let mut all_deps = BTreeMap::<(PackageStr, &Version), Vec<(Option<&str>, Option<&str>)>>::new();
                     for dep in &package.deps {
-                        all_deps.entry((dep.name, &dep.version)).or_default().push(None);
+                        all_deps.entry((dep.name, &dep.version)).or_default().push((None, dep.target));
                     }
                     for dep in &package.build_deps {
-                        all_deps.entry((dep.name, &dep.version)).or_default().push(Some("build"));
+                        all_deps.entry((dep.name, &dep.version)).or_default().push((Some("build"), dep.target));
                     }
                     for dep in &package.dev_deps {
-                        all_deps.entry((dep.name, &dep.version)).or_default().push(Some("dev"));
+                        all_deps.entry((dep.name, &dep.version)).or_default().push((Some("dev"), dep.target));
                     }
                     json!({
                         "id": self.pkgid(package),
@@ -660,9 +901,9 @@ impl MockMetadata {
                         "deps": all_deps.iter().map(|((name, version), kinds)| json!({
                             "name": name,
                             "pkg": self.pkgid_by(name, version),
-                            "dep_kinds": kinds.iter().map(|kind| json!({
+                            "dep_kinds": kinds.iter().map(|(kind, target)| json!({
                                 "kind": kind,
-                                "target": null,
+                                "target": target,
                             })).collect::<Vec<_>>(),
                         })).collect::<Vec<_>>(),
                     })
@@ -828,6 +1069,15 @@ fn builtin_files_minimal_audited(metadata: &Metadata) -> (ConfigFile, AuditsFile
     (config, audits, imports)
 }
 
+/// A store of the given files that also imports `foreign` as "peer".
+fn peer_store(
+    (config, audits, imports): (ConfigFile, AuditsFile, ImportsFile),
+    import: RemoteImport,
+    foreign: AuditsFile,
+) -> Store {
+    Store::mock(config, audits, imports).with_import("peer".to_owned(), import, foreign)
+}
+
 fn mock_cfg(metadata: &Metadata) -> Config {
     Config {
         metacfg: MetaConfig(vec![]),
@@ -841,6 +1091,25 @@ fn mock_cfg(metadata: &Metadata) -> Config {
     }
 }
 
+/// A config for a real store in `dir` (see [`write_store`][]).
+fn disk_cfg(metadata: &Metadata, dir: &Path) -> Config {
+    let mut cfg = mock_cfg(metadata);
+    cfg.metacfg = MetaConfig(vec![MetaConfigInstance {
+        version: Some(1),
+        store: Some(StoreInfo {
+            path: Some(dir.to_owned()),
+        }),
+    }]);
+    cfg
+}
+
+/// Write `store` out to `dir`, so it can be acquired with [`disk_cfg`][].
+fn write_store(dir: &Path, store: Store) {
+    for (file, contents) in store.mock_commit(false) {
+        fs::write(dir.join(file), contents).unwrap();
+    }
+}
+
 fn get_report(metadata: &Metadata, report: ResolveReport) -> String {
     let cfg = mock_cfg(metadata);
     let mut output = Vec::new();
@@ -848,6 +1117,16 @@ fn get_report(metadata: &Metadata, report: ResolveReport) -> String {
     String::from_utf8(output).unwrap()
 }
 
+/// Like [`get_report`][], but the json report. This is `--frozen`, so that
+/// it doesn't try to fetch anything for suggestions.
+fn get_report_json(metadata: &Metadata, report: ResolveReport) -> String {
+    let mut cfg = mock_cfg(metadata);
+    cfg._rest.cli.frozen = true;
+    let mut output = Vec::new();
+    report.print_json(&mut output, &cfg).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
 fn get_unaudited(store: &Store) -> String {
     toml::ser::to_string_pretty(&store.config.unaudited).unwrap()
 }
@@ -870,7 +1149,7 @@ fn mock_simple_init() {
     let (config, audits, imports) = files_inited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-init", output);
 }
@@ -885,7 +1164,7 @@ fn mock_simple_no_unaudited() {
     let (config, audits, imports) = files_no_unaudited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-no-unaudited", output);
@@ -901,7 +1180,7 @@ fn mock_simple_full_audited() {
     let (config, audits, imports) = files_full_audited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-full-audited", output);
@@ -917,7 +1196,7 @@ fn builtin_simple_init() {
     let (config, audits, imports) = builtin_files_inited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-init", output);
 }
@@ -932,7 +1211,7 @@ fn builtin_simple_no_unaudited() {
     let (config, audits, imports) = builtin_files_no_unaudited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-no-unaudited", output);
@@ -948,7 +1227,7 @@ fn builtin_simple_full_audited() {
     let (config, audits, imports) = builtin_files_full_audited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-full-audited", output);
@@ -971,7 +1250,7 @@ fn mock_simple_violation_cur_unaudited() {
         .push(violation_hard(violation));
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-violation-cur-unaudited", output);
@@ -996,7 +1275,7 @@ fn mock_simple_violation_cur_full_audit() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-violation-cur-full-audit", output);
@@ -1023,7 +1302,7 @@ fn mock_simple_violation_delta() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-violation-delta", output);
@@ -1050,7 +1329,7 @@ fn mock_simple_violation_full_audit() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-violation-full-audit", output);
@@ -1075,7 +1354,7 @@ fn mock_simple_violation_wildcard() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-violation-wildcard", output);
@@ -1097,7 +1376,7 @@ fn mock_simple_missing_transitive() {
         .clear();
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-missing-transitive", output);
@@ -1115,7 +1394,7 @@ fn mock_simple_missing_direct_internal() {
     audits.audits.get_mut("third-party1").unwrap().clear();
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-missing-direct-internal", output);
@@ -1133,7 +1412,7 @@ fn mock_simple_missing_direct_leaf() {
     audits.audits.get_mut("third-party2").unwrap().clear();
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-missing-direct-leaf", output);
@@ -1156,7 +1435,7 @@ fn mock_simple_missing_leaves() {
         .clear();
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-missing-leaves", output);
@@ -1184,7 +1463,7 @@ fn mock_simple_weaker_transitive_req() {
     ));
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-weaker-transitive-req", output);
@@ -1213,7 +1492,7 @@ fn mock_simple_weaker_transitive_req_using_implies() {
     ));
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-weaker-transitive-req-using-implies", output);
@@ -1233,7 +1512,7 @@ fn mock_simple_lower_version_review() {
     direct_audits.push(full_audit(ver(DEFAULT_VER - 1), DEFAULT_CRIT));
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-lower-version-review", output);
@@ -1253,7 +1532,7 @@ fn mock_simple_higher_version_review() {
     direct_audits.push(full_audit(ver(DEFAULT_VER + 1), DEFAULT_CRIT));
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-higher-version-review", output);
@@ -1276,7 +1555,7 @@ fn mock_simple_higher_and_lower_version_review() {
     direct_audits.push(full_audit(ver(DEFAULT_VER + 1), DEFAULT_CRIT));
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-higher-and-lower-version-review", output);
@@ -1296,7 +1575,7 @@ fn mock_simple_reviewed_too_weakly() {
     trans_audits.push(full_audit(ver(DEFAULT_VER), "weak-reviewed"));
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-reviewed-too-weakly", output);
@@ -1326,7 +1605,7 @@ fn mock_simple_delta_to_unaudited() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-delta-to-unaudited", output);
@@ -1356,7 +1635,7 @@ fn mock_simple_delta_to_unaudited_overshoot() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-delta-to-unaudited-overshoot", output);
@@ -1386,7 +1665,7 @@ fn mock_simple_delta_to_unaudited_undershoot() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-delta-to-unaudited-undershoot", output);
@@ -1411,7 +1690,7 @@ fn mock_simple_delta_to_full_audit() {
     direct_audits.push(full_audit(ver(DEFAULT_VER - 5), DEFAULT_CRIT));
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-delta-to-full-audit", output);
@@ -1436,7 +1715,7 @@ fn mock_simple_delta_to_full_audit_overshoot() {
     direct_audits.push(full_audit(ver(DEFAULT_VER - 5), DEFAULT_CRIT));
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-delta-to-full-audit-overshoot", output);
@@ -1461,7 +1740,7 @@ fn mock_simple_delta_to_full_audit_undershoot() {
     direct_audits.push(full_audit(ver(DEFAULT_VER - 5), DEFAULT_CRIT));
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-delta-to-full-audit-undershoot", output);
@@ -1486,7 +1765,7 @@ fn mock_simple_reverse_delta_to_full_audit() {
     direct_audits.push(full_audit(ver(DEFAULT_VER + 5), DEFAULT_CRIT));
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-reverse-delta-to-full-audit", output);
@@ -1516,7 +1795,7 @@ fn mock_simple_reverse_delta_to_unaudited() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-reverse-delta-to-unaudited", output);
@@ -1546,7 +1825,7 @@ fn mock_simple_wrongly_reversed_delta_to_unaudited() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-wrongly-reversed-delta-to-unaudited", output);
@@ -1571,7 +1850,7 @@ fn mock_simple_wrongly_reversed_delta_to_full_audit() {
     direct_audits.push(full_audit(ver(DEFAULT_VER - 5), DEFAULT_CRIT));
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-wrongly-reversed-delta-to-full-audit", output);
@@ -1601,7 +1880,7 @@ fn mock_simple_needed_reversed_delta_to_unaudited() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-needed-reversed-delta-to-unaudited", output);
@@ -1631,7 +1910,7 @@ fn mock_simple_delta_to_unaudited_too_weak() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-delta-to-unaudited-too-weak", output);
@@ -1656,7 +1935,7 @@ fn mock_simple_delta_to_full_audit_too_weak() {
     direct_audits.push(full_audit(ver(DEFAULT_VER - 5), DEFAULT_CRIT));
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-delta-to-full-audit-too-weak", output);
@@ -1681,7 +1960,7 @@ fn mock_simple_delta_to_too_weak_full_audit() {
     direct_audits.push(full_audit(ver(DEFAULT_VER - 5), "weak-reviewed"));
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-simple-delta-to-too-weak-full-audit", output);
@@ -1696,7 +1975,7 @@ fn mock_complex_inited() {
     let (config, audits, imports) = files_inited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-complex-inited", output);
 }
@@ -1710,7 +1989,7 @@ fn mock_complex_no_unaudited() {
     let (config, audits, imports) = files_no_unaudited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-complex-no-unaudited", output);
 }
@@ -1724,7 +2003,7 @@ fn mock_complex_full_audited() {
     let (config, audits, imports) = files_full_audited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-complex-full-audited", output);
 }
@@ -1738,7 +2017,7 @@ fn builtin_complex_inited() {
     let (config, audits, imports) = builtin_files_inited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-complex-inited", output);
 }
@@ -1752,7 +2031,7 @@ fn builtin_complex_no_unaudited() {
     let (config, audits, imports) = builtin_files_no_unaudited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-complex-no-unaudited", output);
 }
@@ -1766,7 +2045,7 @@ fn builtin_complex_full_audited() {
     let (config, audits, imports) = builtin_files_full_audited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-complex-full-audited", output);
 }
@@ -1780,7 +2059,7 @@ fn builtin_complex_minimal_audited() {
     let (config, audits, imports) = builtin_files_minimal_audited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-complex-minimal-audited", output);
 }
@@ -1799,7 +2078,7 @@ fn mock_complex_missing_core5() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-complex-missing-core5", output);
 }
@@ -1818,7 +2097,7 @@ fn mock_complex_missing_core10() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-complex-missing-core10", output);
 }
@@ -1840,7 +2119,7 @@ fn mock_complex_core10_too_weak() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-complex-core10-too-weak", output);
 }
@@ -1874,7 +2153,7 @@ fn mock_complex_core10_partially_too_weak() {
         .insert("thirdAB".to_string(), vec![audit_with_weaker_req]);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("mock-complex-core10-partially-too-weak", output);
 }
@@ -1908,7 +2187,7 @@ fn mock_complex_core10_partially_too_weak_via_weak_delta() {
         .insert("thirdAB".to_string(), vec![audit_with_weaker_req]);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!(
         "mock-complex-core10-partially-too-weak-via-weak-delta",
@@ -1951,7 +2230,7 @@ fn mock_complex_core10_partially_too_weak_via_strong_delta() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!(
         "mock-complex-core10-partially-too-weak-via-strong-delta",
@@ -1972,7 +2251,7 @@ fn mock_simple_policy_root_too_strong() {
         .insert("root-package".to_string(), self_policy(["strong-reviewed"]));
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("simple-policy-root-too-strong", output);
 }
@@ -1990,7 +2269,7 @@ fn mock_simple_policy_root_weaker() {
         .insert("root-package".to_string(), self_policy(["weak-reviewed"]));
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("simple-policy-root-weaker", output);
 }
@@ -2008,7 +2287,7 @@ fn mock_simple_policy_first_too_strong() {
         .insert("first-party".to_string(), self_policy(["strong-reviewed"]));
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("simple-policy-first-too-strong", output);
 }
@@ -2026,7 +2305,7 @@ fn mock_simple_policy_first_weaker() {
         .insert("first-party".to_string(), self_policy(["weak-reviewed"]));
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("simple-policy-first-weaker", output);
 }
@@ -2045,7 +2324,7 @@ fn mock_simple_policy_root_dep_weaker() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("simple-policy-root-dep-weaker", output);
 }
@@ -2064,7 +2343,7 @@ fn mock_simple_policy_root_dep_too_strong() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("simple-policy-root-dep-too-strong", output);
 }
@@ -2083,7 +2362,7 @@ fn mock_simple_policy_first_dep_weaker() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("simple-policy-first-dep-weaker", output);
 }
@@ -2102,7 +2381,7 @@ fn mock_simple_policy_first_dep_too_strong() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("simple-policy-first-dep-too-strong", output);
 }
@@ -2126,7 +2405,7 @@ fn mock_simple_policy_first_dep_stronger() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("simple-policy-first-dep-stronger", output);
 }
@@ -2150,7 +2429,7 @@ fn mock_simple_policy_first_dep_weaker_needed() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("simple-policy-first-dep-weaker-needed", output);
 }
@@ -2177,7 +2456,7 @@ fn mock_simple_policy_first_dep_extra() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("simple-policy-first-dep-extra", output);
 }
@@ -2201,7 +2480,7 @@ fn mock_simple_policy_first_dep_extra_missing() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("simple-policy-first-dep-extra-missing", output);
 }
@@ -2228,7 +2507,7 @@ fn mock_simple_policy_first_extra_partially_missing() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("simple-policy-first-extra-partially-missing", output);
 }
@@ -2247,7 +2526,7 @@ fn mock_simple_first_policy_redundant() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("simple-policy-first-policy-redundant", output);
 }
@@ -2261,7 +2540,7 @@ fn builtin_simple_deps_inited() {
     let (config, audits, imports) = builtin_files_inited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-deps-init", output);
 }
@@ -2276,7 +2555,7 @@ fn builtin_simple_deps_no_unaudited() {
     let (config, audits, imports) = builtin_files_no_unaudited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-deps-no-unaudited", output);
@@ -2292,7 +2571,7 @@ fn builtin_simple_deps_full_audited() {
     let (config, audits, imports) = builtin_files_full_audited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-deps-full-audited", output);
@@ -2308,7 +2587,7 @@ fn builtin_simple_deps_minimal_audited() {
     let (config, audits, imports) = builtin_files_minimal_audited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-deps-minimal-audited", output);
@@ -2329,7 +2608,7 @@ fn builtin_no_deps() {
     let (config, audits, imports) = builtin_files_full_audited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-no-deps", output);
@@ -2358,7 +2637,7 @@ fn builtin_only_first_deps() {
     let (config, audits, imports) = builtin_files_full_audited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-only-first-deps", output);
@@ -2373,7 +2652,7 @@ fn builtin_cycle_inited() {
     let (config, audits, imports) = builtin_files_inited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-cycle-inited", output);
 }
@@ -2388,7 +2667,7 @@ fn builtin_cycle_unaudited() {
     let (config, audits, imports) = builtin_files_no_unaudited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-cycle-unaudited", output);
@@ -2404,7 +2683,7 @@ fn builtin_cycle_full_audited() {
     let (config, audits, imports) = builtin_files_full_audited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-cycle-full-audited", output);
@@ -2420,7 +2699,7 @@ fn builtin_cycle_minimal_audited() {
     let (config, audits, imports) = builtin_files_minimal_audited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-cycle-minimal-audited", output);
@@ -2461,7 +2740,7 @@ fn builtin_dev_detection() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-dev-detection", output);
@@ -2477,7 +2756,7 @@ fn builtin_dev_detection_empty() {
     let (config, audits, imports) = builtin_files_no_unaudited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-dev-detection-empty", output);
@@ -2493,7 +2772,7 @@ fn builtin_dev_detection_empty_deeper() {
     let (config, audits, imports) = builtin_files_no_unaudited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, true);
+    let report = crate::resolver::resolve(&metadata, None, &store, true).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-dev-detection-empty-deeper", output);
@@ -2520,7 +2799,7 @@ fn builtin_simple_unaudited_extra() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-unaudited-extra", output);
@@ -2570,7 +2849,7 @@ fn builtin_simple_unaudited_not_a_real_dep() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-not-a-real-dep", output);
@@ -2617,7 +2896,7 @@ fn builtin_simple_deps_unaudited_overbroad() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-unaudited-overbroad", output);
@@ -2668,7 +2947,7 @@ fn builtin_complex_unaudited_twins() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-unaudited-twins", output);
@@ -2722,7 +3001,7 @@ fn builtin_complex_unaudited_partial_twins() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-unaudited-partial-twins", output);
@@ -2784,7 +3063,7 @@ fn builtin_simple_unaudited_in_delta() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-unaudited-in-delta", output);
@@ -2847,7 +3126,7 @@ fn builtin_simple_unaudited_in_full() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-unaudited-in-full", output);
@@ -2905,7 +3184,7 @@ fn builtin_simple_unaudited_in_direct_full() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-unaudited-in-direct-full", output);
@@ -2992,7 +3271,7 @@ fn builtin_simple_unaudited_nested_weaker_req() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-unaudited-nested-weaker-req", output);
@@ -3044,7 +3323,7 @@ fn builtin_simple_unaudited_nested_weaker_req_needs_dep_criteria() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!(
@@ -3163,7 +3442,7 @@ fn builtin_simple_unaudited_nested_stronger_req() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-unaudited-nested-stronger-req", output);
@@ -3253,7 +3532,7 @@ fn builtin_simple_deps_unaudited_adds_uneeded_criteria() {
         .insert("dev".to_string(), vec![unaudited(ver(5), SAFE_TO_DEPLOY)]);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!(
@@ -3320,7 +3599,7 @@ fn builtin_dev_detection_unaudited_adds_uneeded_criteria_indirect() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!(
@@ -3387,7 +3666,7 @@ fn builtin_dev_detection_cursed_full() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-dev-detection-cursed-full", output);
@@ -3412,7 +3691,7 @@ fn builtin_dev_detection_cursed_minimal() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-dev-detection-cursed-minimal", output);
@@ -3439,7 +3718,7 @@ fn builtin_simple_delta_cycle() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-delta-cycle", output);
@@ -3467,7 +3746,7 @@ fn builtin_simple_noop_delta() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-noop-delta", output);
@@ -3498,7 +3777,7 @@ fn builtin_simple_delta_double_cycle() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-delta-double-cycle", output);
@@ -3529,7 +3808,7 @@ fn builtin_simple_delta_broken_double_cycle() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-delta-broken-double-cycle", output);
@@ -3557,7 +3836,7 @@ fn builtin_simple_delta_broken_cycle() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-delta-broken-cycle", output);
@@ -3585,7 +3864,7 @@ fn builtin_simple_long_cycle() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-long-cycle", output);
@@ -3613,7 +3892,7 @@ fn builtin_simple_useless_long_cycle() {
     );
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-useless-long-cycle", output);
@@ -3629,7 +3908,7 @@ fn builtin_haunted_init() {
     let (config, audits, imports) = builtin_files_inited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-haunted-init", output);
 }
@@ -3644,7 +3923,7 @@ fn builtin_haunted_no_unaudited() {
     let (config, audits, imports) = builtin_files_no_unaudited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-haunted-no-unaudited", output);
@@ -3660,7 +3939,7 @@ fn builtin_haunted_no_unaudited_deeper() {
     let (config, audits, imports) = builtin_files_no_unaudited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, true);
+    let report = crate::resolver::resolve(&metadata, None, &store, true).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-haunted-no-unaudited-deeper", output);
@@ -3676,7 +3955,7 @@ fn builtin_haunted_full_audited() {
     let (config, audits, imports) = builtin_files_full_audited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-haunted-full-audited", output);
@@ -3692,50 +3971,2437 @@ fn builtin_haunted_minimal_audited() {
     let (config, audits, imports) = builtin_files_minimal_audited(&metadata);
 
     let store = Store::mock(config, audits, imports);
-    let report = crate::resolver::resolve(&metadata, None, &store, false);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-haunted-minimal-audited", output);
 }
 
-// TESTING BACKLOG:
-//
-// * custom policies
-//   * basic
-//   * custom criteria to third-party
-//   * custom criteria to first-party
-//   * two first-parties depending on the same thing
-//      * which is itself first-party
-//      * which is a third-party
-//      * with different policies
-//         * where only the weaker one is satisfied (fail but give good diagnostic)
-//
-// * foreign mappings
-//   * only using builtins
-//   * 1:1 explicit mappings
-//   * asymmetric cases
-//   * missing mappings
-//   * foreign has criteria with the same name, unmapped (don't accidentally mix it up)
-//   * foreign has criteria with the same name, mapped to that name
-//   * foreign has criteria with the same name, mapped to a different name
-//
-// * misc
-//   * git deps are first party but not in workspace
-//   * path deps are first party but not in workspace
-//   * multiple root packages
-//   * weird workspaces
-//   * running from weird directories
-//   * a node explicitly setting all its dependency_criteria to "no reqs"
-//     * ...should this just be an error? that feels wrong to do. otherwise:
-//       * with perfectly fine children
-//       * with children that fail to validate at all
-//
-// * malformed inputs:
-//   * no default criteria specified
-//   * referring to non-existent criteria
-//   * referring to non-existent crates (in crates.io? or just in our dep graph?)
-//   * referring to non-existent versions?
-//   * Bad delta syntax
-//   * Bad version syntax
-//   * entries in tomls that don't map to anything (at least warn to catch typos?)
-//     * might be running an old version of cargo-vet on a newer repo?
+#[test]
+fn mock_simple_validate_inited() {
+    // (Pass) A freshly inited store is valid.
+
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (config, audits, imports) = files_inited(&metadata);
+
+    let store = Store::mock(config, audits, imports);
+    store.validate(Some(&metadata)).unwrap();
+}
+
+#[test]
+fn mock_simple_validate_errors() {
+    // (Fail) Every kind of mistake is found and reported at once.
+
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (mut config, mut audits, imports) = files_inited(&metadata);
+
+    audits.criteria.insert(
+        "safe-to-run".to_string(),
+        CriteriaEntry {
+            implies: vec![],
+            description: Some("shadowed".to_string()),
+            description_url: None,
+        },
+    );
+    audits.criteria.insert(
+        "undescribed".to_string(),
+        CriteriaEntry {
+            implies: vec!["reviewd".to_string()],
+            description: None,
+            description_url: None,
+        },
+    );
+    audits.criteria.insert(
+        "loopy".to_string(),
+        CriteriaEntry {
+            implies: vec!["loopier".to_string()],
+            description: Some("loopy".to_string()),
+            description_url: None,
+        },
+    );
+    audits.criteria.insert(
+        "loopier".to_string(),
+        CriteriaEntry {
+            implies: vec!["loopy".to_string()],
+            description: Some("loopier".to_string()),
+            description_url: None,
+        },
+    );
+    audits.audits.insert(
+        "third-party1".to_string(),
+        vec![
+            full_audit(ver(DEFAULT_VER), "safe-to-deplyo"),
+            delta_audit(ver(5), ver(5), DEFAULT_CRIT),
+            full_audit_dep(
+                ver(3),
+                DEFAULT_CRIT,
+                [("transitive-third-party1", ["fuzzd"])],
+            ),
+        ],
+    );
+    config.default_criteria = "reviewd".to_string();
+    config
+        .policy
+        .insert("third-party2".to_string(), self_policy(["strong-reviewd"]));
+    config.unaudited.insert(
+        "fake-dep".to_string(),
+        vec![unaudited(ver(1), DEFAULT_CRIT)],
+    );
+    config
+        .unaudited
+        .insert("first-party".to_string(), vec![unaudited(ver(1), "weak")]);
+
+    let store = Store::mock(config, audits, imports);
+    let errors = store.validate(Some(&metadata)).unwrap_err();
+    insta::assert_snapshot!("mock-simple-validate-errors", errors.to_string());
+}
+
+#[test]
+fn mock_validate_error_locations() {
+    // (Fail) Errors in hand-edited files point at the offending line.
+
+    let config = r#"
+default-criteria = "reviewed"
+
+[imports.peer]
+url = "https://peer.example.com/audits.toml"
+criteria-map = [
+    { ours = "reviewed", theirs = "safe-to-deploy" },
+    { ours = "reviewd", theirs = ["safe-to-run", "fuzzd"] },
+]
+
+[policy.first-party]
+criteria = ["reviewed", "reveiwed"]
+dependency-criteria = { third-party1 = "fuzzed" }
+
+[[unaudited.third-party1]]
+version = "10.0.0"
+criteria = "safe-to-run"
+
+[[unaudited.third-party1]]
+version = "9.0.0"
+criteria = "safe-to-rnu"
+"#;
+    let audits = r#"
+[criteria.reviewed]
+description = "reviewed"
+
+[criteria.fuzzed]
+description = "fuzzed"
+implies = "safe-to-rnu"
+
+[[audits.third-party1]]
+criteria = "safe-to-deplyo"
+version = "10.0.0"
+
+[[audits.third-party2]]
+criteria = "reviewed"
+delta = "1.0.0 -> 1.0.0"
+"#;
+    let imports = r#"
+[audits.peer.criteria.fuzzed]
+description = "fuzzed"
+
+[[audits.peer.audits.third-party1]]
+criteria = "fuzzed"
+version = "10.0.0"
+dependency-criteria = { transitive-third-party1 = ["fuzzed", "fuzed"] }
+"#;
+
+    let store = Store::mock_from_sources(config, audits, imports).unwrap();
+    let errors = store.validate(None).unwrap_err();
+    insta::assert_snapshot!("mock-validate-error-locations", errors.to_string());
+}
+
+#[test]
+fn mock_validate_untidy_import() {
+    // (Pass) Untidiness in a peer's audits only warns, but audits we can't
+    // understand still fail.
+
+    let config = r#"
+[imports.peer]
+url = "https://example.com/supply-chain/audits.toml"
+criteria-map = []
+"#;
+    let imports = r#"
+[audits.peer.criteria.safe-to-run]
+description = "shadowed"
+
+[audits.peer.criteria.undescribed]
+
+[[audits.peer.audits.third-party1]]
+criteria = "undescribed"
+delta = "1.0.0 -> 1.0.0"
+"#;
+    let store = Store::mock_from_sources(config, "[audits]", imports).unwrap();
+    store.validate(None).unwrap();
+
+    let imports = format!(
+        r#"{imports}
+[[audits.peer.audits.third-party2]]
+criteria = "undefined"
+version = "1.0.0"
+"#
+    );
+    let store = Store::mock_from_sources(config, "[audits]", &imports).unwrap();
+    let errors = store.validate(None).unwrap_err();
+    assert_eq!(errors.errors.len(), 1, "{errors}");
+}
+
+#[test]
+fn mock_parse_error_location() {
+    // (Fail) Parse errors (like merge-conflict damage) point at the offending line.
+
+    let audits = r#"
+[criteria.reviewed]
+description = "reviewed"
+
+<<<<<<< HEAD
+[[audits.third-party1]]
+criteria = "reviewed"
+version = "10.0.0"
+"#;
+
+    let error = match Store::mock_from_sources("", audits, "[audits]") {
+        Ok(_) => panic!("merge conflict markers should fail to parse"),
+        Err(error) => error,
+    };
+    insta::assert_snapshot!("mock-parse-error-location", error.to_string());
+}
+
+const HAND_EDITED_CONFIG: &str = r#"format-version = 2
+
+# Our policy is to be very careful
+default-criteria = "reviewed"
+
+[policy.first-party]
+criteria = ["reviewed"] # but not fuzzed, yet
+
+# This one is fine for now, see bug 123
+[[unaudited.third-party1]]
+version = "10.0.0"
+criteria = "reviewed"
+"#;
+
+const HAND_EDITED_AUDITS: &str = r#"format-version = 2
+
+# cargo-vet audits file
+
+[criteria.reviewed]
+description = "reviewed"
+implies = "safe-to-deploy"
+
+# Alice looked at this one, ask her about it
+[[audits.third-party2]]
+who = "Alice <alice@example.com>"
+criteria = "reviewed"
+version = "10.0.0"
+notes = """
+This is fine.
+
+Really!
+"""
+
+[[audits.transitive-third-party1]]
+criteria = "safe-to-run"
+version = "10.0.0" # the only version we use
+"#;
+
+const HAND_EDITED_IMPORTS: &str = r#"format-version = 2
+
+[audits]
+"#;
+
+#[test]
+fn mock_commit_preserves_layout() {
+    // Committing a hand-edited store only touches the entries that changed.
+
+    let mut store =
+        Store::mock_from_sources(HAND_EDITED_CONFIG, HAND_EDITED_AUDITS, HAND_EDITED_IMPORTS)
+            .unwrap();
+    store.config.unaudited.remove("third-party1");
+    store.audits.audits.insert(
+        "third-party1".to_string(),
+        vec![full_audit(ver(DEFAULT_VER), "reviewed")],
+    );
+    store
+        .audits
+        .audits
+        .get_mut("third-party2")
+        .unwrap()
+        .push(delta_audit(ver(DEFAULT_VER), ver(11), "reviewed"));
+
+    let files = store.mock_commit(false);
+    insta::assert_snapshot!("mock-commit-preserves-layout-config", files["config.toml"]);
+    insta::assert_snapshot!("mock-commit-preserves-layout-audits", files["audits.toml"]);
+}
+
+#[test]
+fn disk_commit_partial_failure() {
+    // (Fail) A commit that can't replace every file says how far it got, and
+    // leaves no temporary files behind.
+
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (config, audits, imports) = files_inited(&metadata);
+    let dir = tempfile::tempdir().unwrap();
+    write_store(dir.path(), Store::mock(config, audits, imports));
+    let cfg = disk_cfg(&metadata, dir.path());
+
+    let store = Store::acquire(&cfg).unwrap();
+    // Renaming over a directory with something in it always fails
+    fs::remove_file(dir.path().join("imports.lock")).unwrap();
+    fs::create_dir_all(dir.path().join("imports.lock").join("blocker")).unwrap();
+
+    let error = format!("{:#}", store.commit().unwrap_err());
+    assert!(
+        error.starts_with("the store was only partially updated (already replaced: audits.toml)"),
+        "{error}"
+    );
+    let mut files = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    files.sort();
+    assert_eq!(files, ["audits.toml", "config.toml", "imports.lock"]);
+}
+
+#[test]
+fn disk_commit_read_only() {
+    // (Fail) A store acquired for reading only can't be committed.
+
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (config, audits, imports) = files_inited(&metadata);
+    let dir = tempfile::tempdir().unwrap();
+    write_store(dir.path(), Store::mock(config, audits, imports));
+    let cfg = disk_cfg(&metadata, dir.path());
+    let before = fs::read_to_string(dir.path().join("config.toml")).unwrap();
+
+    let mut store = Store::acquire_ro(&cfg).unwrap();
+    store.config.default_criteria = SAFE_TO_RUN.to_owned();
+    let error = store.commit().unwrap_err().to_string();
+    assert_eq!(error, "tried to commit a store that was acquired read-only");
+
+    let after = fs::read_to_string(dir.path().join("config.toml")).unwrap();
+    assert_eq!(before, after);
+}
+
+#[test]
+fn mock_commit_unchanged_is_byte_for_byte() {
+    // Committing a hand-edited store that didn't change doesn't touch it.
+
+    let store =
+        Store::mock_from_sources(HAND_EDITED_CONFIG, HAND_EDITED_AUDITS, HAND_EDITED_IMPORTS)
+            .unwrap();
+    let files = store.mock_commit(false);
+    assert_eq!(files["config.toml"], HAND_EDITED_CONFIG);
+    assert_eq!(files["audits.toml"], HAND_EDITED_AUDITS);
+}
+
+#[test]
+fn mock_commit_reformatted_keeps_comments() {
+    // Reformatting a hand-edited store puts it in the usual layout, but keeps comments.
+
+    let store =
+        Store::mock_from_sources(HAND_EDITED_CONFIG, HAND_EDITED_AUDITS, HAND_EDITED_IMPORTS)
+            .unwrap();
+    let files = store.mock_commit(true);
+    insta::assert_snapshot!("mock-commit-reformatted-config", files["config.toml"]);
+    insta::assert_snapshot!("mock-commit-reformatted-audits", files["audits.toml"]);
+}
+
+#[test]
+fn mock_migrate_unversioned() {
+    // A store from before format versions were recorded is upgraded in place.
+
+    let config = HAND_EDITED_CONFIG.replace("format-version = 2\n", "");
+    let audits = HAND_EDITED_AUDITS.replace("format-version = 2\n", "");
+    let store = Store::mock_from_sources(&config, &audits, "[audits]").unwrap();
+    assert_eq!(store.migrated_from(), Some(1));
+
+    let files = store.mock_commit(false);
+    assert_eq!(
+        files["config.toml"],
+        format!("format-version = 2\n{config}")
+    );
+    assert_eq!(
+        files["audits.toml"],
+        format!("format-version = 2\n{audits}")
+    );
+    insta::assert_snapshot!("mock-migrate-unversioned-imports", files["imports.lock"]);
+}
+
+#[test]
+fn mock_migrate_current() {
+    // A store in the current format doesn't need migrating.
+
+    let store =
+        Store::mock_from_sources(HAND_EDITED_CONFIG, HAND_EDITED_AUDITS, HAND_EDITED_IMPORTS)
+            .unwrap();
+    assert_eq!(store.migrated_from(), None);
+}
+
+#[test]
+fn mock_migrate_map_builtins() {
+    // Imports from before built-ins were mapped by default keep not mapping
+    // them, unless they already said otherwise.
+
+    let config = r#"
+[imports.peer]
+url = "https://example.com/supply-chain/audits.toml"
+
+[imports.other]
+url = "https://example.org/supply-chain/audits.toml"
+map-builtins = true
+"#;
+    let store = Store::mock_from_sources(config, HAND_EDITED_AUDITS, HAND_EDITED_IMPORTS).unwrap();
+    assert_eq!(store.migrated_from(), Some(1));
+    assert!(!store.config.imports["peer"].map_builtins);
+    assert!(store.config.imports["other"].map_builtins);
+
+    let files = store.mock_commit(false);
+    insta::assert_snapshot!("mock-migrate-map-builtins", files["config.toml"]);
+
+    // Stores from version 2 on take the default.
+    let config = format!("format-version = 2\n{config}");
+    let store = Store::mock_from_sources(&config, HAND_EDITED_AUDITS, HAND_EDITED_IMPORTS).unwrap();
+    assert!(store.config.imports["peer"].map_builtins);
+}
+
+#[test]
+fn mock_migrate_from_the_future() {
+    // (Fail) A store in a newer format than we understand is rejected.
+
+    let audits = HAND_EDITED_AUDITS.replace("format-version = 2", "format-version = 1000");
+    let error = match Store::mock_from_sources(HAND_EDITED_CONFIG, &audits, HAND_EDITED_IMPORTS) {
+        Ok(_) => panic!("a newer format should be rejected"),
+        Err(error) => error,
+    };
+    insta::assert_snapshot!("mock-migrate-from-the-future", error.to_string());
+}
+
+const PEER_CONFIG: &str = r#"
+[imports.peer]
+url = "https://example.com/supply-chain/audits.toml"
+criteria-map = [{ theirs = "strong-reviewed", ours = "safe-to-deploy" }]
+"#;
+
+const PEER_IMPORTS: &str = r#"
+[audits.peer.criteria.reviewed]
+description = "someone looked at it"
+
+[audits.peer.criteria.strong-reviewed]
+description = "someone looked at it carefully"
+implies = "reviewed"
+
+[audits.peer.criteria.fuzzed]
+description = "fuzzed for a while"
+
+[audits.peer.audits]
+"#;
+
+fn peer_imports_changed(from: &str, to: &str) -> ImportsFile {
+    let imports = PEER_IMPORTS.replace(from, to);
+    assert_ne!(imports, PEER_IMPORTS);
+    toml::from_str(&imports).unwrap()
+}
+
+#[test]
+fn mock_criteria_change_rejected() {
+    // (Fail) A foreign criteria we map (or one it implies) changing its
+    // definition is an error, which shows what changed.
+
+    let mut store = Store::mock_from_sources(PEER_CONFIG, "[audits]", PEER_IMPORTS).unwrap();
+    let new_imports = peer_imports_changed("someone looked at it\"", "someone glanced at it\"");
+    let error = store.update_imports(new_imports, false).unwrap_err();
+    insta::assert_snapshot!("mock-criteria-change-rejected", error.to_string());
+
+    // Nothing was updated
+    assert_eq!(
+        store.imports.audits["peer"].criteria["reviewed"]
+            .description
+            .as_deref(),
+        Some("someone looked at it")
+    );
+}
+
+#[test]
+fn mock_criteria_change_accepted() {
+    // (Pass) Accepting a change to a criteria's definition records the new one.
+
+    let mut store = Store::mock_from_sources(PEER_CONFIG, "[audits]", PEER_IMPORTS).unwrap();
+    let new_imports = peer_imports_changed("implies = \"reviewed\"\n", "");
+    let changes = store.update_imports(new_imports, true).unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].criteria_name, "strong-reviewed");
+    assert!(store.imports.audits["peer"].criteria["strong-reviewed"]
+        .implies
+        .is_empty());
+
+    // Now that we've accepted it, it's not a change anymore
+    let new_imports = peer_imports_changed("implies = \"reviewed\"\n", "");
+    store.update_imports(new_imports, false).unwrap();
+}
+
+#[test]
+fn mock_criteria_change_unmapped() {
+    // (Pass) Changes to foreign criteria we don't rely on don't matter.
+
+    let mut store = Store::mock_from_sources(PEER_CONFIG, "[audits]", PEER_IMPORTS).unwrap();
+    let new_imports = peer_imports_changed("fuzzed for a while", "fuzzed for a second");
+    let changes = store.update_imports(new_imports, false).unwrap();
+    assert!(changes.is_empty());
+}
+
+#[test]
+fn mock_fetch_time_unchanged_import() {
+    // (Pass) An import that hasn't changed keeps the time it was fetched, so
+    // imports.lock isn't rewritten on every fetch, but a changed one doesn't.
+
+    let imports = format!("[fetched]\npeer = 1000\n{PEER_IMPORTS}");
+    let mut store = Store::mock_from_sources(PEER_CONFIG, "[audits]", &imports).unwrap();
+
+    let mut new_imports: ImportsFile = toml::from_str(PEER_IMPORTS).unwrap();
+    new_imports.fetched.insert("peer".to_owned(), 5000);
+    store.update_imports(new_imports, false).unwrap();
+    assert_eq!(store.imports.fetched["peer"], 1000);
+
+    let mut new_imports = peer_imports_changed("fuzzed for a while", "fuzzed for a second");
+    new_imports.fetched.insert("peer".to_owned(), 5000);
+    store.update_imports(new_imports, false).unwrap();
+    assert_eq!(store.imports.fetched["peer"], 5000);
+}
+
+/// Serve a single HTTP response with this body on a local port, returning
+/// the URL to fetch it from.
+fn serve_once(body: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/audits.toml", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0; 1024];
+        let _ = stream.read(&mut request);
+        let _ = write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+    });
+    url
+}
+
+#[test]
+fn mock_fetch_imports_reports_every_failure() {
+    // (Fail) Imports are fetched together, and every one that fails is
+    // reported, rather than just the first.
+
+    // Accepts connections but never responds, so it should time out
+    let silent = TcpListener::bind("127.0.0.1:0").unwrap();
+    let silent_url = format!("http://{}/audits.toml", silent.local_addr().unwrap());
+    let good_url = serve_once("[audits]\n");
+    let garbage_url = serve_once("<html>not an audits.toml</html>");
+
+    let config = format!(
+        r#"
+[imports.good]
+url = "{good_url}"
+criteria-map = []
+
+[imports.garbage]
+url = "{garbage_url}"
+criteria-map = []
+
+[imports.silent]
+url = "{silent_url}"
+criteria-map = []
+"#
+    );
+    let mut store = Store::mock_from_sources(&config, "[audits]", "[audits]").unwrap();
+    let metadata = MockMetadata::simple().metadata();
+    let mut cfg = mock_cfg(&metadata);
+    cfg._rest.cli.fetch_timeout = 1;
+    let error = store.fetch_foreign_audits(&cfg).unwrap_err().to_string();
+
+    assert!(error.starts_with("failed to fetch 2 imports"), "{error}");
+    assert!(
+        error.contains(&format!("could not parse garbage @ {garbage_url}")),
+        "{error}"
+    );
+    assert!(
+        error.contains(&format!("could not load silent @ {silent_url}")),
+        "{error}"
+    );
+    assert!(!error.contains("good"), "{error}");
+}
+
+#[test]
+fn mock_fetch_imports_allow_stale() {
+    // (Pass) With stale imports allowed, an import we can't reach keeps its
+    // copy in imports.lock, and the others are still updated.
+
+    // Nothing is listening here, so the connection is refused
+    let closed = TcpListener::bind("127.0.0.1:0").unwrap();
+    let closed_url = format!("http://{}/audits.toml", closed.local_addr().unwrap());
+    drop(closed);
+    let good_url = serve_once(
+        r#"
+[[audits.third-party1]]
+criteria = "safe-to-deploy"
+version = "10.0.0"
+"#,
+    );
+
+    let config = format!(
+        r#"
+allow-stale-imports = true
+
+[imports.good]
+url = "{good_url}"
+criteria-map = []
+
+[imports.closed]
+url = "{closed_url}"
+criteria-map = []
+"#
+    );
+    let imports = r#"
+[fetched]
+good = 1000
+closed = 2000
+
+[audits.good.audits]
+
+[[audits.closed.audits.third-party2]]
+criteria = "safe-to-deploy"
+version = "10.0.0"
+"#;
+    let mut store = Store::mock_from_sources(&config, "[audits]", imports).unwrap();
+    let metadata = MockMetadata::simple().metadata();
+    let cfg = mock_cfg(&metadata);
+    store.fetch_foreign_audits(&cfg).unwrap();
+
+    assert!(store.imports.audits["good"]
+        .audits
+        .contains_key("third-party1"));
+    assert!(store.imports.audits["closed"]
+        .audits
+        .contains_key("third-party2"));
+    // The fetch times live in imports.lock, so they're the same on every
+    // machine. The stale copy keeps its old one.
+    assert!(store.imports.fetched["good"] > 2000);
+    assert_eq!(store.imports.fetched["closed"], 2000);
+
+    // But we can't fall back to an import we've never fetched (`good` can't
+    // be fetched again either, but falls back to what we just got)
+    store.imports.audits.remove("closed");
+    let error = store.fetch_foreign_audits(&cfg).unwrap_err().to_string();
+    assert!(error.starts_with("failed to fetch 1 imports"), "{error}");
+    assert!(
+        error.contains(&format!("could not load closed @ {closed_url}")),
+        "{error}"
+    );
+}
+
+#[test]
+fn mock_fetch_imports_stale_pin_mismatch() {
+    // (Fail) Allowing stale imports doesn't let an import that doesn't match its
+    // pin fall back to the copy in imports.lock.
+
+    let url = serve_once("[audits]\n");
+    let config = format!(
+        r#"
+allow-stale-imports = true
+
+[imports.pinned]
+url = "{url}"
+criteria-map = []
+sha256 = "{}"
+"#,
+        "0".repeat(64)
+    );
+    let imports = r#"
+[fetched]
+pinned = 2000
+
+[[audits.pinned.audits.third-party2]]
+criteria = "safe-to-deploy"
+version = "10.0.0"
+"#;
+    let mut store = Store::mock_from_sources(&config, "[audits]", imports).unwrap();
+    let metadata = MockMetadata::simple().metadata();
+    let cfg = mock_cfg(&metadata);
+    let error = store.fetch_foreign_audits(&cfg).unwrap_err().to_string();
+
+    assert!(error.starts_with("failed to fetch 1 imports"), "{error}");
+    assert!(
+        error.contains(&format!("could not verify pinned @ {url}")),
+        "{error}"
+    );
+    assert!(store.imports.audits["pinned"]
+        .audits
+        .contains_key("third-party2"));
+}
+
+#[test]
+fn mock_fetch_imports_local() {
+    // (Pass) Imports can be read from relative paths and file:// URLs.
+
+    let audits_path = "tests/test-project/supply-chain/audits.toml";
+    let audits_url =
+        reqwest::Url::from_file_path(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(audits_path))
+            .unwrap();
+    let config = format!(
+        r#"
+[imports.relative]
+url = "{audits_path}"
+criteria-map = []
+
+[imports.file-url]
+url = "{audits_url}"
+criteria-map = []
+
+[imports.missing]
+url = "does/not/exist/audits.toml"
+criteria-map = []
+"#
+    );
+
+    // Mock stores resolve relative paths against the current directory, which
+    // is the root of this crate when running tests.
+    let store = Store::mock_from_sources(&config, "[audits]", "[audits]").unwrap();
+    let FetchedImports {
+        imports, errors, ..
+    } = store.fetch_imports(Duration::from_secs(1), true);
+    let expected = fs::read_to_string(audits_path).unwrap();
+    let expected: AuditsFile = toml::from_str(&expected).unwrap();
+    for name in ["relative", "file-url"] {
+        assert_eq!(
+            imports.audits[name].audits.keys().collect::<Vec<_>>(),
+            expected.audits.keys().collect::<Vec<_>>()
+        );
+    }
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].import_name, "missing");
+}
+
+#[test]
+fn sha256_test_vectors() {
+    assert_eq!(
+        sha256_hex(b""),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert_eq!(
+        sha256_hex(b"abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+}
+
+#[test]
+fn mock_fetch_imports_pinned() {
+    // (Fail) An import whose contents don't match its pin is refused, until
+    // it's re-pinned.
+
+    let config = r#"
+[imports.pinned]
+url = "tests/test-project/supply-chain/audits.toml"
+criteria-map = []
+sha256 = "0000000000000000000000000000000000000000000000000000000000000000"
+"#;
+    let mut store = Store::mock_from_sources(config, "[audits]", "[audits]").unwrap();
+    let metadata = MockMetadata::simple().metadata();
+    let cfg = mock_cfg(&metadata);
+    let error = store.fetch_foreign_audits(&cfg).unwrap_err().to_string();
+    assert!(
+        error.contains("could not verify pinned @ tests/test-project/supply-chain/audits.toml - expected sha256 0000"),
+        "{error}"
+    );
+    assert!(store.imports.audits.is_empty());
+
+    let expected = sha256_hex(&fs::read("tests/test-project/supply-chain/audits.toml").unwrap());
+    let changed = store.pin_imports(&cfg).unwrap();
+    assert_eq!(changed.len(), 1);
+    assert_eq!(
+        store.config.imports["pinned"].sha256.as_deref(),
+        Some(&*expected)
+    );
+    assert!(store.imports.audits.contains_key("pinned"));
+
+    // Now it matches, so fetching works (and re-pinning changes nothing)
+    store.fetch_foreign_audits(&cfg).unwrap();
+    assert!(store.pin_imports(&cfg).unwrap().is_empty());
+}
+
+#[test]
+fn mock_validate_bad_pin() {
+    // (Fail) Pins must be sha256 hashes.
+
+    let config = r#"
+[imports.pinned]
+url = "https://example.com/supply-chain/audits.toml"
+criteria-map = []
+sha256 = "d3adb33f"
+"#;
+    let store = Store::mock_from_sources(config, "[audits]", "[audits]").unwrap();
+    let errors = store.validate(None).unwrap_err();
+    insta::assert_snapshot!("mock-validate-bad-pin", errors.to_string());
+}
+
+#[test]
+fn mock_registry() {
+    // (Pass) The registry we ship parses, and can be read from a local path.
+
+    let metadata = MockMetadata::simple().metadata();
+    let cfg = mock_cfg(&metadata);
+    let registry = storage::fetch_registry(&cfg, "registry.toml").unwrap();
+    assert!(registry.registry.contains_key("firefox"));
+
+    let error = match storage::fetch_registry(&cfg, "does/not/exist/registry.toml") {
+        Ok(_) => panic!("a missing registry should fail"),
+        Err(error) => error.to_string(),
+    };
+    assert!(
+        error.starts_with("could not load registry @ does/not/exist/registry.toml"),
+        "{error}"
+    );
+}
+
+#[test]
+fn builtin_simple_foreign_mapped_implied() {
+    // (Pass) A foreign audit mapped to our safe-to-deploy is also safe-to-run,
+    // so it satisfies dependencies that only need to be safe-to-run.
+
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+
+    let mut foreign = empty_audits();
+    for package in &mock.packages {
+        if !package.is_first_party {
+            foreign.audits.insert(
+                package.name.to_owned(),
+                vec![full_audit(package.version.clone(), SAFE_TO_DEPLOY)],
+            );
+        }
+    }
+    let import = RemoteImport {
+        criteria_map: vec![CriteriaMapping {
+            ours: SAFE_TO_DEPLOY.to_owned(),
+            theirs: vec![SAFE_TO_DEPLOY.to_owned()],
+        }],
+        map_builtins: false,
+        ..remote_import()
+    };
+
+    let store = peer_store(builtin_files_no_unaudited(&metadata), import, foreign);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("builtin-simple-foreign-mapped-implied", output);
+}
+
+#[test]
+fn mock_suggest_import() {
+    // (Pass) Suggest notices which failures an audit set would vet if imported.
+
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (config, audits, imports) = builtin_files_no_unaudited(&metadata);
+    let cfg = mock_cfg(&metadata);
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let suggest = report.compute_suggest(&cfg, true).unwrap().unwrap();
+
+    let mut foreign = empty_audits();
+    for name in ["third-party1", "transitive-third-party1"] {
+        foreign.audits.insert(
+            name.to_owned(),
+            vec![full_audit(ver(DEFAULT_VER), SAFE_TO_DEPLOY)],
+        );
+    }
+    let import = RemoteImport {
+        criteria_map: vec![CriteriaMapping {
+            ours: SAFE_TO_DEPLOY.to_owned(),
+            theirs: vec![SAFE_TO_DEPLOY.to_owned()],
+        }],
+        map_builtins: false,
+        ..remote_import()
+    };
+    let hypothetical = store.with_import("peer".to_owned(), import.clone(), foreign);
+    let import_suggest = report
+        .evaluate_import(
+            &cfg,
+            &hypothetical,
+            false,
+            "peer".to_owned(),
+            import.url,
+            &suggest.suggestions,
+        )
+        .unwrap();
+
+    let mut vetted = import_suggest
+        .vetted
+        .iter()
+        .map(|&pkgidx| report.graph.nodes[pkgidx].name)
+        .collect::<Vec<_>>();
+    vetted.sort();
+    assert_eq!(vetted, ["third-party1"]);
+    let expected_lines: u64 = suggest
+        .suggestions
+        .iter()
+        .filter(|item| vetted.contains(&report.graph.nodes[item.package].name))
+        .map(|item| item.suggested_diff.diffstat.count)
+        .sum();
+    assert_eq!(import_suggest.lines, expected_lines);
+
+    // An audit set that doesn't vet anything isn't worth suggesting.
+    let empty = empty_audits();
+    let hypothetical = store.with_import(
+        "useless".to_owned(),
+        RemoteImport {
+            url: "https://example.com/useless.toml".to_owned(),
+            ..remote_import()
+        },
+        empty,
+    );
+    assert!(report
+        .evaluate_import(
+            &cfg,
+            &hypothetical,
+            false,
+            "useless".to_owned(),
+            "https://example.com/useless.toml".to_owned(),
+            &suggest.suggestions,
+        )
+        .is_none());
+}
+
+#[test]
+fn mock_aggregate() {
+    // (Pass) Audits are merged and tagged with where they came from, and
+    // identical audits are only kept once.
+
+    let mut first = empty_audits();
+    first.criteria.insert(
+        "reviewed".to_owned(),
+        CriteriaEntry {
+            implies: vec![],
+            description: Some("reviewed".to_owned()),
+            description_url: None,
+        },
+    );
+    first
+        .audits
+        .insert("both".to_owned(), vec![full_audit(ver(1), "reviewed")]);
+    first
+        .audits
+        .insert("first".to_owned(), vec![full_audit(ver(1), SAFE_TO_DEPLOY)]);
+
+    let mut second = first.clone();
+    second.audits.remove("first");
+    second
+        .audits
+        .get_mut("both")
+        .unwrap()
+        .push(delta_audit(ver(1), ver(2), "reviewed"));
+    let mut already_aggregated = full_audit(ver(3), SAFE_TO_RUN);
+    already_aggregated.aggregated_from = Some("https://example.com/original.toml".to_owned());
+    second
+        .audits
+        .insert("second".to_owned(), vec![already_aggregated]);
+
+    let merged = storage::merge_audits(vec![
+        ("https://example.com/first.toml".to_owned(), first),
+        ("../second/audits.toml".to_owned(), second),
+    ])
+    .unwrap();
+
+    let mut output = Vec::new();
+    storage::write_audits(&mut output, merged).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    insta::assert_snapshot!("mock-aggregate", output);
+}
+
+#[test]
+fn mock_aggregate_targets() {
+    // (Pass) Audits that only differ in their targets are both kept.
+
+    let mut restricted = full_audit(ver(1), SAFE_TO_DEPLOY);
+    restricted.targets = Some(targets(&["cfg(unix)"]));
+    let source = |entry: AuditEntry| AuditsFile {
+        criteria: SortedMap::new(),
+        audits: [("package".to_owned(), vec![entry])].into_iter().collect(),
+    };
+
+    let merged = storage::merge_audits(vec![
+        (
+            "https://example.com/first.toml".to_owned(),
+            source(restricted.clone()),
+        ),
+        (
+            "https://example.com/second.toml".to_owned(),
+            source(full_audit(ver(1), SAFE_TO_DEPLOY)),
+        ),
+        (
+            "https://example.com/third.toml".to_owned(),
+            source(restricted),
+        ),
+    ])
+    .unwrap();
+
+    let entries = &merged.audits["package"];
+    assert_eq!(entries.len(), 2);
+    assert!(entries[0].targets.is_some());
+    assert!(entries[1].targets.is_none());
+}
+
+#[test]
+fn mock_aggregate_conflict() {
+    // (Fail) Sources that define a criteria differently can't be merged.
+
+    let source = |description: &str| {
+        let mut audits = empty_audits();
+        audits.criteria.insert(
+            "reviewed".to_owned(),
+            CriteriaEntry {
+                implies: vec![SAFE_TO_RUN.to_owned()],
+                description: Some(description.to_owned()),
+                description_url: None,
+            },
+        );
+        audits
+    };
+
+    let result = storage::merge_audits(vec![
+        (
+            "https://example.com/first.toml".to_owned(),
+            source("reviewed"),
+        ),
+        (
+            "https://example.com/second.toml".to_owned(),
+            source("reviewed"),
+        ),
+        (
+            "https://example.com/third.toml".to_owned(),
+            source("looked at it"),
+        ),
+    ]);
+    let error = match result {
+        Ok(_) => panic!("conflicting criteria should fail"),
+        Err(error) => error.to_string(),
+    };
+    insta::assert_snapshot!("mock-aggregate-conflict", error);
+}
+
+#[test]
+fn mock_aggregate_sources() {
+    // (Fail) Sources are read from local paths relative to the sources list,
+    // and all the ones that can't be fetched are reported.
+
+    let dir = tempfile::tempdir().unwrap();
+    let dir = dir.path();
+    let audits_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/test-project/supply-chain/audits.toml");
+    fs::copy(audits_path, dir.join("audits.toml")).unwrap();
+    let sources_path = dir.join("sources.list");
+
+    let metadata = MockMetadata::simple().metadata();
+    let cfg = mock_cfg(&metadata);
+
+    fs::write(&sources_path, "# our audits\naudits.toml\n\n").unwrap();
+    let merged = storage::aggregate(&cfg, &sources_path).unwrap();
+    assert!(merged
+        .audits
+        .values()
+        .flatten()
+        .all(|entry| entry.aggregated_from.as_deref() == Some("audits.toml")));
+
+    fs::write(
+        &sources_path,
+        "audits.toml\nmissing.toml\nalso-missing.toml\n",
+    )
+    .unwrap();
+    let error = match storage::aggregate(&cfg, &sources_path) {
+        Ok(_) => panic!("missing sources should fail"),
+        Err(error) => error.to_string(),
+    };
+    assert!(error.starts_with("failed to fetch 2 sources"), "{error}");
+    assert!(error.contains("could not load missing.toml"), "{error}");
+    assert!(
+        error.contains("could not load also-missing.toml"),
+        "{error}"
+    );
+}
+
+fn foreign_dep_criteria_store(
+    metadata: &Metadata,
+    criteria_map: Vec<CriteriaMapping>,
+    map_builtins: bool,
+) -> Store {
+    let (config, audits, imports) = builtin_files_no_unaudited(metadata);
+
+    // third-party1 is safe-to-deploy as long as transitive-third-party1 is
+    // safe-to-run.
+    let mut foreign = empty_audits();
+    foreign.audits.insert(
+        "third-party1".to_owned(),
+        vec![full_audit_dep(
+            ver(DEFAULT_VER),
+            SAFE_TO_DEPLOY,
+            [("transitive-third-party1", [SAFE_TO_RUN])],
+        )],
+    );
+    foreign.audits.insert(
+        "transitive-third-party1".to_owned(),
+        vec![full_audit(ver(DEFAULT_VER), SAFE_TO_RUN)],
+    );
+    foreign.audits.insert(
+        "third-party2".to_owned(),
+        vec![full_audit(ver(DEFAULT_VER), SAFE_TO_DEPLOY)],
+    );
+
+    let import = RemoteImport {
+        criteria_map,
+        map_builtins,
+        ..remote_import()
+    };
+    peer_store((config, audits, imports), import, foreign)
+}
+
+#[test]
+fn builtin_simple_foreign_dep_criteria() {
+    // (Pass) Foreign dependency-criteria are mapped to our criteria.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let criteria_map = [SAFE_TO_DEPLOY, SAFE_TO_RUN]
+        .into_iter()
+        .map(|criteria| CriteriaMapping {
+            ours: criteria.to_owned(),
+            theirs: vec![criteria.to_owned()],
+        })
+        .collect();
+    let store = foreign_dep_criteria_store(&metadata, criteria_map, false);
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("builtin-simple-foreign-dep-criteria", output);
+}
+
+/// A foreign store that defines crypto-reviewed (implying safe-to-deploy), where
+/// the audit of third-party1 requires transitive-third-party1 to be crypto-reviewed.
+fn foreign_crypto_reviewed_audits() -> AuditsFile {
+    let mut foreign = empty_audits();
+    foreign.criteria.insert(
+        "crypto-reviewed".to_owned(),
+        CriteriaEntry {
+            implies: vec![SAFE_TO_DEPLOY.to_owned()],
+            description: Some("crypto-reviewed".to_owned()),
+            description_url: None,
+        },
+    );
+    foreign.audits.insert(
+        "third-party1".to_owned(),
+        vec![full_audit_dep(
+            ver(DEFAULT_VER),
+            SAFE_TO_DEPLOY,
+            [("transitive-third-party1", ["crypto-reviewed"])],
+        )],
+    );
+    foreign
+}
+
+#[test]
+fn builtin_simple_foreign_dep_criteria_unmapped() {
+    // (Fail) An audit with dependency-criteria that can't be mapped to our criteria
+    // doesn't vouch for anything, but the rest of the import is still used.
+    //
+    // Their crypto-reviewed implies safe-to-deploy, but that doesn't make our
+    // safe-to-deploy good enough when they require crypto-reviewed.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let mut foreign = foreign_crypto_reviewed_audits();
+    for name in ["transitive-third-party1", "third-party2"] {
+        foreign.audits.insert(
+            name.to_owned(),
+            vec![full_audit(ver(DEFAULT_VER), "crypto-reviewed")],
+        );
+    }
+    let store = peer_store(
+        builtin_files_no_unaudited(&metadata),
+        remote_import(),
+        foreign,
+    );
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("builtin-simple-foreign-dep-criteria-unmapped", output);
+}
+
+#[test]
+fn builtin_simple_foreign_dep_criteria_custom() {
+    // (Fail) A foreign requirement of only a custom criteria is mapped through the
+    // criteria-map, and the dependency still has to meet it.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let (config, mut audits, imports) = builtin_files_no_unaudited(&metadata);
+    audits.criteria.insert(
+        "crypto-reviewed".to_owned(),
+        CriteriaEntry {
+            implies: vec![SAFE_TO_DEPLOY.to_owned()],
+            description: Some("crypto-reviewed".to_owned()),
+            description_url: None,
+        },
+    );
+    for name in ["transitive-third-party1", "third-party2"] {
+        audits.audits.insert(
+            name.to_owned(),
+            vec![full_audit(ver(DEFAULT_VER), SAFE_TO_DEPLOY)],
+        );
+    }
+    let import = RemoteImport {
+        criteria_map: vec![CriteriaMapping {
+            ours: "crypto-reviewed".to_owned(),
+            theirs: vec!["crypto-reviewed".to_owned()],
+        }],
+        ..remote_import()
+    };
+    let store = peer_store(
+        (config.clone(), audits.clone(), imports.clone()),
+        import.clone(),
+        foreign_crypto_reviewed_audits(),
+    );
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let failures = match &report.conclusion {
+        Conclusion::FailForVet(fail) => &fail.failures,
+        _ => panic!("expected a vetting failure"),
+    };
+    let blamed = failures
+        .keys()
+        .map(|&pkgidx| report.graph.nodes[pkgidx].name)
+        .collect::<Vec<_>>();
+    assert_eq!(blamed, ["transitive-third-party1"]);
+
+    // Once the dependency is crypto-reviewed, the audit vouches for third-party1
+    audits.audits.insert(
+        "transitive-third-party1".to_owned(),
+        vec![full_audit(ver(DEFAULT_VER), "crypto-reviewed")],
+    );
+    let store = peer_store(
+        (config, audits, imports),
+        import,
+        foreign_crypto_reviewed_audits(),
+    );
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    assert!(
+        matches!(report.conclusion, Conclusion::Success(..)),
+        "{}",
+        get_report(&metadata, report)
+    );
+}
+
+#[test]
+fn builtin_simple_foreign_implicit_builtins() {
+    // (Pass) Foreign built-in criteria map to ours without a criteria-map.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let store = foreign_dep_criteria_store(&metadata, vec![], true);
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("builtin-simple-foreign-implicit-builtins", output);
+}
+
+#[test]
+fn builtin_simple_foreign_no_implicit_builtins() {
+    // (Fail) Imports can opt out of mapping the built-in criteria.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let store = foreign_dep_criteria_store(&metadata, vec![], false);
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("builtin-simple-foreign-no-implicit-builtins", output);
+}
+
+#[test]
+fn mock_fetch_imports_redefined_builtin() {
+    // (Fail) Imports that map the built-in criteria can't redefine them, as we
+    // assume they mean the same thing as ours.
+
+    let dir = tempfile::tempdir().unwrap();
+    let audits_path = dir.path().join("audits.toml");
+    fs::write(
+        &audits_path,
+        r#"
+[criteria.safe-to-run]
+description = "it compiles"
+
+[audits]
+"#,
+    )
+    .unwrap();
+    let config = format!(
+        r#"format-version = 2
+
+[imports.peer]
+url = "{}"
+"#,
+        audits_path.display()
+    );
+
+    let store = Store::mock_from_sources(&config, "[audits]", "[audits]").unwrap();
+    let FetchedImports { errors, .. } = store.fetch_imports(Duration::from_secs(1), true);
+    assert_eq!(errors.len(), 1);
+    let error = errors[0].to_string();
+    assert!(
+        error.ends_with("it redefines the built-in criteria `safe-to-run`"),
+        "{error}"
+    );
+
+    // (Pass) But those that don't map them can, as their criteria are only
+    // trusted through the criteria-map.
+    let config = format!("{config}map-builtins = false\n");
+    let store = Store::mock_from_sources(&config, "[audits]", "[audits]").unwrap();
+    let FetchedImports {
+        imports, errors, ..
+    } = store.fetch_imports(Duration::from_secs(1), true);
+    assert!(errors.is_empty(), "{errors:?}");
+    assert!(imports.audits["peer"].criteria.contains_key(SAFE_TO_RUN));
+}
+
+fn scoped_import_store(metadata: &Metadata, scope: impl FnOnce(&mut RemoteImport)) -> Store {
+    let (config, audits, imports) = builtin_files_no_unaudited(metadata);
+
+    let mut foreign = empty_audits();
+    for (package, who) in [
+        ("third-party1", "crypto-team"),
+        ("transitive-third-party1", "crypto-team"),
+        ("third-party2", "intern"),
+    ] {
+        let mut audit = full_audit(ver(DEFAULT_VER), SAFE_TO_DEPLOY);
+        audit.who = Some(who.to_owned());
+        foreign.audits.insert(package.to_owned(), vec![audit]);
+    }
+
+    let mut import = remote_import();
+    scope(&mut import);
+    peer_store((config, audits, imports), import, foreign)
+}
+
+#[test]
+fn builtin_simple_foreign_scoped_auditors() {
+    // (Fail) Only audits by the auditors we trust from an import are used.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let store = scoped_import_store(&metadata, |import| {
+        import.auditors = vec!["crypto-team".to_owned()];
+    });
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    assert_eq!(report.filtered_foreign_audits, 1);
+    let json = get_report_json(&metadata, report.clone());
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(json["filtered_foreign_audits"], 1);
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("builtin-simple-foreign-scoped-auditors", output);
+}
+
+#[test]
+fn builtin_simple_foreign_scoped_packages() {
+    // (Fail) Only audits of the packages we trust an import for are used.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+
+    let store = scoped_import_store(&metadata, |import| {
+        import.include = vec!["third-party1".to_owned(), "third-party2".to_owned()];
+        import.exclude = vec!["third-party2".to_owned()];
+    });
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    assert_eq!(report.filtered_foreign_audits, 2);
+    let failures = match &report.conclusion {
+        Conclusion::FailForVet(fail) => fail
+            .failures
+            .keys()
+            .map(|&pkgidx| report.graph.nodes[pkgidx].name)
+            .collect::<Vec<_>>(),
+        _ => panic!("out of scope audits shouldn't be used"),
+    };
+    assert_eq!(failures, ["third-party2", "transitive-third-party1"]);
+
+    let store = scoped_import_store(&metadata, |import| {
+        import.exclude = vec!["nothing-we-use".to_owned()];
+    });
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    assert_eq!(report.filtered_foreign_audits, 0);
+    assert!(matches!(report.conclusion, Conclusion::Success(_)));
+}
+
+#[test]
+fn builtin_simple_foreign_max_criteria() {
+    // (Fail) An import can't grant more than its max-criteria.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let store = scoped_import_store(&metadata, |import| {
+        import.max_criteria = Some(SAFE_TO_RUN.to_owned());
+    });
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("builtin-simple-foreign-max-criteria", output);
+}
+
+fn foreign_violation_store(
+    metadata: &Metadata,
+    acknowledged: Option<AcknowledgedViolation>,
+) -> Store {
+    let (mut config, audits, imports) = builtin_files_full_audited(metadata);
+
+    let violation_range = VersionReq::parse(&format!("={DEFAULT_VER}")).unwrap();
+    let mut foreign = empty_audits();
+    foreign.audits.insert(
+        "third-party2".to_owned(),
+        vec![violation(violation_range, SAFE_TO_DEPLOY)],
+    );
+
+    if let Some(acknowledged) = acknowledged {
+        config
+            .acknowledged_violations
+            .insert("third-party2".to_owned(), vec![acknowledged]);
+    }
+
+    let import = remote_import();
+    peer_store((config, audits, imports), import, foreign)
+}
+
+#[test]
+fn builtin_simple_foreign_violation() {
+    // (Fail) A foreign violation conflicts with our audits.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let store = foreign_violation_store(&metadata, None);
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    assert!(matches!(
+        report.conclusion,
+        Conclusion::FailForViolationConflict(_)
+    ));
+}
+
+#[test]
+fn builtin_simple_foreign_violation_out_of_scope() {
+    // (Fail) Scoping an import only limits what we trust it for, its violations
+    // still apply.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let mut store = foreign_violation_store(&metadata, None);
+    let import = store.config.imports.get_mut("peer").unwrap();
+    import.exclude = vec!["third-party2".to_owned()];
+    import.auditors = vec!["crypto-team".to_owned()];
+    import.max_criteria = Some(SAFE_TO_RUN.to_owned());
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    assert!(matches!(
+        report.conclusion,
+        Conclusion::FailForViolationConflict(_)
+    ));
+    assert_eq!(report.filtered_foreign_audits, 0);
+}
+
+#[test]
+fn builtin_simple_foreign_violation_acknowledged() {
+    // (Pass) A foreign violation we've acknowledged is only a warning.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let store = foreign_violation_store(
+        &metadata,
+        Some(AcknowledgedViolation {
+            import: "peer".to_owned(),
+            violation: VersionReq::parse(&format!("={DEFAULT_VER}")).unwrap(),
+            justification: "we only use the parts they didn't like in tests".to_owned(),
+        }),
+    );
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    assert!(matches!(report.conclusion, Conclusion::Success(_)));
+    assert_eq!(report.acknowledged_violations.len(), 1);
+    let json = get_report_json(&metadata, report.clone());
+    insta::assert_snapshot!("builtin-simple-foreign-violation-acknowledged-json", json);
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("builtin-simple-foreign-violation-acknowledged", output);
+}
+
+#[test]
+fn builtin_simple_foreign_violation_acknowledged_other_range() {
+    // (Fail) Acknowledging a different range doesn't cover the violation.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let store = foreign_violation_store(
+        &metadata,
+        Some(AcknowledgedViolation {
+            import: "peer".to_owned(),
+            violation: VersionReq::parse(">=1.0.0").unwrap(),
+            justification: "we only use the parts they didn't like in tests".to_owned(),
+        }),
+    );
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    assert!(matches!(
+        report.conclusion,
+        Conclusion::FailForViolationConflict(_)
+    ));
+    assert!(report.acknowledged_violations.is_empty());
+}
+
+#[test]
+fn mock_validate_acknowledged_violations() {
+    // (Fail) Acknowledged violations need a known import and a justification.
+
+    let config = r#"
+[[acknowledged-violations.third-party1]]
+import = "nobody"
+violation = "=10.0.0"
+justification = " "
+"#;
+    let store = Store::mock_from_sources(config, "[audits]", "[audits]").unwrap();
+    let errors = store.validate(None).unwrap_err();
+    insta::assert_snapshot!("mock-validate-acknowledged-violations", errors.to_string());
+}
+
+fn targets_store(
+    metadata: &Metadata,
+    project_targets: Option<&[&str]>,
+    audit_targets: &[&str],
+) -> Store {
+    let (mut config, mut audits, imports) = builtin_files_full_audited(metadata);
+
+    config.targets = project_targets.map(targets);
+    for audit in audits.audits.get_mut("third-party1").unwrap() {
+        audit.targets = Some(targets(audit_targets));
+    }
+
+    Store::mock(config, audits, imports)
+}
+
+#[test]
+fn builtin_simple_audit_targets_uncovered() {
+    // (Fail) An audit restricted to a platform we don't build for doesn't count.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let store = targets_store(
+        &metadata,
+        Some(&["x86_64-unknown-linux-gnu"]),
+        &["cfg(windows)"],
+    );
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("builtin-simple-audit-targets-uncovered", output);
+}
+
+#[test]
+fn builtin_simple_audit_targets_covered() {
+    // (Pass) An audit covering every platform we build for counts.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    for audit_targets in [
+        &["cfg(unix)"][..],
+        &["x86_64-unknown-linux-gnu", "aarch64-apple-darwin"][..],
+    ] {
+        let store = targets_store(
+            &metadata,
+            Some(&["x86_64-unknown-linux-gnu", "aarch64-apple-darwin"]),
+            audit_targets,
+        );
+        let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+        assert!(
+            matches!(report.conclusion, Conclusion::Success(_)),
+            "{audit_targets:?} should cover our targets"
+        );
+    }
+}
+
+#[test]
+fn builtin_simple_audit_targets_partial() {
+    // (Fail) Audits have to cover every platform we build for, and if we don't
+    // say which those are, that's all of them.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    for project_targets in [
+        Some(&["x86_64-unknown-linux-gnu", "x86_64-pc-windows-msvc"][..]),
+        None,
+    ] {
+        let store = targets_store(&metadata, project_targets, &["cfg(unix)"]);
+        let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+        assert!(
+            matches!(report.conclusion, Conclusion::FailForVet(_)),
+            "cfg(unix) shouldn't cover {project_targets:?}"
+        );
+    }
+}
+
+#[test]
+fn builtin_simple_audit_targets_unknown() {
+    // (Fail) Without validation to catch it first, an unknown project target is
+    // an error when resolving, rather than a panic when matching audits to it.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let store = targets_store(
+        &metadata,
+        Some(&["x86_64-unknown-linux-gnu", "not-a-real-target"]),
+        &["cfg(unix)"],
+    );
+
+    let error = match crate::resolver::resolve(&metadata, None, &store, false) {
+        Ok(_) => panic!("resolving with an unknown target should fail"),
+        Err(error) => error.to_string(),
+    };
+    assert!(
+        error.contains("couldn't get the cfgs for the target `not-a-real-target`"),
+        "{error}"
+    );
+}
+
+#[test]
+fn mock_validate_targets() {
+    // (Fail) Targets have to parse, and the project's have to be triples.
+
+    let config = r#"
+targets = ["x86_64-unknown-linux-gnu", "cfg(unix)"]
+
+[policy.root]
+dev-targets = "cfg(windows)"
+"#;
+    let audits = r#"
+[[audits.third-party1]]
+criteria = "safe-to-deploy"
+version = "10.0.0"
+targets = "cfg(unix"
+"#;
+    let store = Store::mock_from_sources(config, audits, "[audits]").unwrap();
+    let errors = store.validate(None).unwrap_err();
+    insta::assert_snapshot!("mock-validate-targets", errors.to_string());
+}
+
+#[test]
+fn mock_validate_unknown_target() {
+    // (Fail) Target triples have to be ones rustc knows the cfgs of.
+
+    let config = r#"
+[policy.root]
+targets = ["x86_64-unknown-linux-gnu", "not-a-real-target"]
+"#;
+    let store = Store::mock_from_sources(config, "[audits]", "[audits]").unwrap();
+    let errors = store.validate(None).unwrap_err().to_string();
+    assert!(
+        errors.contains(
+            "config.toml:3:40: policy for `root` (targets) has an unknown target `not-a-real-target`"
+        ),
+        "{errors}"
+    );
+    assert!(!errors.contains("x86_64-unknown-linux-gnu`"), "{errors}");
+}
+
+#[test]
+fn builtin_platform_deps_unknown_target() {
+    // (Fail) Without validation to catch it first, an unknown target is an
+    // error when resolving, not a panic.
+
+    let mock = MockMetadata::platform_deps();
+    let metadata = mock.metadata();
+    let store = platform_deps_store(&metadata, Some(&["not-a-real-target"]), None);
+
+    let error = match crate::resolver::resolve(&metadata, None, &store, false) {
+        Ok(_) => panic!("resolving with an unknown target should fail"),
+        Err(error) => error.to_string(),
+    };
+    assert!(
+        error.contains("couldn't get the cfgs for the target `not-a-real-target`"),
+        "{error}"
+    );
+}
+
+fn platform_deps_store(
+    metadata: &Metadata,
+    policy_targets: Option<&[&str]>,
+    dev_targets: Option<&[&str]>,
+) -> Store {
+    let (mut config, mut audits, imports) = builtin_files_no_unaudited(metadata);
+
+    for package in ["third-party", "libc"] {
+        audits.audits.insert(
+            package.to_owned(),
+            vec![full_audit(ver(DEFAULT_VER), SAFE_TO_DEPLOY)],
+        );
+    }
+
+    config.policy.insert(
+        "root".to_owned(),
+        PolicyEntry {
+            targets: policy_targets.map(targets),
+            dev_targets: dev_targets.map(targets),
+            ..default_policy()
+        },
+    );
+
+    Store::mock(config, audits, imports)
+}
+
+#[test]
+fn builtin_platform_deps_all_targets() {
+    // (Fail) Without targets, dependencies for every platform need vetting.
+
+    let mock = MockMetadata::platform_deps();
+    let metadata = mock.metadata();
+    let store = platform_deps_store(&metadata, None, None);
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("builtin-platform-deps-all-targets", output);
+}
+
+#[test]
+fn builtin_platform_deps_policy_targets() {
+    // (Pass) Dependencies that aren't active on the policy's targets are left
+    // out of the graph, along with everything only they depend on.
+
+    let mock = MockMetadata::platform_deps();
+    let metadata = mock.metadata();
+    let linux = ["x86_64-unknown-linux-gnu"];
+    let store = platform_deps_store(&metadata, Some(&linux), Some(&linux));
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    assert!(matches!(report.conclusion, Conclusion::Success(_)));
+    let mut names = report
+        .graph
+        .nodes
+        .iter()
+        .map(|node| node.name)
+        .collect::<Vec<_>>();
+    names.sort_unstable();
+    assert_eq!(names, ["libc", "root", "third-party"]);
+}
+
+#[test]
+fn builtin_platform_deps_dev_targets() {
+    // (Fail) dev-targets apply to dev-dependencies separately.
+
+    let mock = MockMetadata::platform_deps();
+    let metadata = mock.metadata();
+    let store = platform_deps_store(
+        &metadata,
+        Some(&["x86_64-unknown-linux-gnu"]),
+        Some(&["wasm32-unknown-unknown"]),
+    );
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("builtin-platform-deps-dev-targets", output);
+}
+
+#[test]
+fn builtin_simple_many_criteria() {
+    // (Fail) More than 64 criteria work, including implies across the
+    // boundary.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let (mut config, mut audits, imports) = builtin_files_full_audited(&metadata);
+
+    // criteria-N implies criteria-(N-1)
+    for idx in 0..100 {
+        audits.criteria.insert(
+            format!("criteria-{idx}"),
+            CriteriaEntry {
+                implies: (idx > 0)
+                    .then(|| format!("criteria-{}", idx - 1))
+                    .into_iter()
+                    .collect(),
+                description: Some(format!("criteria {idx}")),
+                description_url: None,
+            },
+        );
+    }
+    for (package, criteria) in [
+        ("third-party1", "criteria-99"),
+        ("transitive-third-party1", "criteria-99"),
+        ("third-party2", "criteria-40"),
+    ] {
+        audits.audits.insert(
+            package.to_owned(),
+            vec![full_audit(ver(DEFAULT_VER), criteria)],
+        );
+    }
+    config.policy.insert(
+        "root-package".to_owned(),
+        PolicyEntry {
+            criteria: Some(vec!["criteria-80".to_owned()]),
+            ..default_policy()
+        },
+    );
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("builtin-simple-many-criteria", output);
+}
+
+/// A set of `count` criteria with `indices` set.
+fn criteria_set(count: usize, indices: &[usize]) -> CriteriaSet {
+    let mut set = CriteriaSet::none(count);
+    for &idx in indices {
+        set.set_criteria(idx);
+    }
+    set
+}
+
+fn criteria_indices(set: &CriteriaSet) -> Vec<usize> {
+    set.indices().collect()
+}
+
+#[test]
+fn criteria_set_word_boundary() {
+    // Sets work the same on either side of 64 criteria, where they move from
+    // a single inline word to the heap.
+
+    let inline = criteria_set(64, &[0, 1, 63]);
+    let heap = criteria_set(128, &[1, 63, 64, 127]);
+    assert_eq!(criteria_indices(&inline), [0, 1, 63]);
+    assert_eq!(criteria_indices(&heap), [1, 63, 64, 127]);
+    assert!(heap.has_criteria(64) && !heap.has_criteria(65) && !heap.has_criteria(1000));
+    assert_eq!(
+        criteria_indices(&CriteriaSet::_all(64)),
+        (0..64).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        criteria_indices(&CriteriaSet::_all(65)),
+        (0..65).collect::<Vec<_>>()
+    );
+    assert!(CriteriaSet::none(128).is_empty());
+
+    // Setting a criteria past the end grows the set.
+    let mut grown = criteria_set(64, &[3]);
+    grown.set_criteria(100);
+    assert_eq!(criteria_indices(&grown), [3, 100]);
+
+    // Union
+    let mut set = inline.clone();
+    set.unioned_with(&heap);
+    assert_eq!(criteria_indices(&set), [0, 1, 63, 64, 127]);
+    let mut set = heap.clone();
+    set.unioned_with(&inline);
+    assert_eq!(criteria_indices(&set), [0, 1, 63, 64, 127]);
+
+    // Intersection
+    let mut set = inline.clone();
+    set.intersected_with(&heap);
+    assert_eq!(criteria_indices(&set), [1, 63]);
+    let mut set = heap.clone();
+    set.intersected_with(&inline);
+    assert_eq!(criteria_indices(&set), [1, 63]);
+    let mut set = criteria_set(128, &[1, 100]);
+    set.intersected_with(&criteria_set(128, &[100, 101]));
+    assert_eq!(criteria_indices(&set), [100]);
+
+    // Clearing
+    let mut set = heap.clone();
+    set.clear_criteria(&inline);
+    assert_eq!(criteria_indices(&set), [64, 127]);
+    let mut set = inline.clone();
+    set.clear_criteria(&heap);
+    assert_eq!(criteria_indices(&set), [0]);
+
+    // Containment, including heap sets that only use their first word
+    assert!(heap.contains(&criteria_set(64, &[1, 63])));
+    assert!(!heap.contains(&inline));
+    assert!(!inline.contains(&heap));
+    assert!(inline.contains(&criteria_set(128, &[0, 63])));
+    assert!(!inline.contains(&criteria_set(128, &[0, 64])));
+    assert!(inline.contains(&CriteriaSet::none(128)));
+    assert!(CriteriaSet::none(128).contains(&CriteriaSet::none(64)));
+}
+
+// TESTING BACKLOG:
+//
+// * custom policies
+//   * basic
+//   * custom criteria to third-party
+//   * custom criteria to first-party
+//   * two first-parties depending on the same thing
+//      * which is itself first-party
+//      * which is a third-party
+//      * with different policies
+//         * where only the weaker one is satisfied (fail but give good diagnostic)
+//
+// * foreign mappings
+//   * only using builtins
+//   * 1:1 explicit mappings
+//   * asymmetric cases
+//   * missing mappings
+//   * foreign has criteria with the same name, unmapped (don't accidentally mix it up)
+//   * foreign has criteria with the same name, mapped to that name
+//   * foreign has criteria with the same name, mapped to a different name
+//
+// * misc
+//   * git deps are first party but not in workspace
+//   * path deps are first party but not in workspace
+//   * multiple root packages
+//   * weird workspaces
+//   * running from weird directories
+//   * a node explicitly setting all its dependency_criteria to "no reqs"
+//     * ...should this just be an error? that feels wrong to do. otherwise:
+//       * with perfectly fine children
+//       * with children that fail to validate at all
+//
+// * malformed inputs:
+//   * no default criteria specified
+//   * referring to non-existent crates (in crates.io? or just in our dep graph?)
+//   * referring to non-existent versions?
+//   * Bad delta syntax
+//   * Bad version syntax
+//   * entries in tomls that don't map to anything (at least warn to catch typos?)
+//     * might be running an old version of cargo-vet on a newer repo?
+
+fn explain_store(metadata: &Metadata, third_party2: Option<UnauditedDependency>) -> Store {
+    let (mut config, mut audits, imports) = builtin_files_full_audited(metadata);
+
+    audits.audits.insert(
+        "third-party1".to_owned(),
+        vec![
+            AuditEntry {
+                who: Some("alice".to_owned()),
+                ..full_audit(ver(5), SAFE_TO_DEPLOY)
+            },
+            AuditEntry {
+                who: Some("bob".to_owned()),
+                ..delta_audit(ver(5), ver(DEFAULT_VER), SAFE_TO_DEPLOY)
+            },
+        ],
+    );
+    audits.audits.remove("transitive-third-party1");
+    audits.audits.insert(
+        "third-party2".to_owned(),
+        vec![full_audit(ver(5), SAFE_TO_DEPLOY)],
+    );
+    if let Some(entry) = third_party2 {
+        config
+            .unaudited
+            .insert("third-party2".to_owned(), vec![entry]);
+    }
+
+    let mut foreign = empty_audits();
+    foreign.audits.insert(
+        "transitive-third-party1".to_owned(),
+        vec![AuditEntry {
+            who: Some("carol".to_owned()),
+            ..full_audit(ver(DEFAULT_VER), SAFE_TO_DEPLOY)
+        }],
+    );
+    let import = remote_import();
+    peer_store((config, audits, imports), import, foreign)
+}
+
+fn get_explain(report: &ResolveReport, names: &[&str]) -> String {
+    let mut output = Vec::new();
+    for name in names {
+        let pkgidx = report
+            .graph
+            .nodes
+            .iter()
+            .position(|package| package.name == *name)
+            .unwrap();
+        report.print_explain_human(&mut output, pkgidx).unwrap();
+    }
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn builtin_simple_explain() {
+    // (Pass) Explain the own, foreign and unaudited audits that vet each package.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let store = explain_store(&metadata, Some(unaudited(ver(DEFAULT_VER), SAFE_TO_DEPLOY)));
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    assert!(matches!(report.conclusion, Conclusion::Success(_)));
+    let output = get_explain(
+        &report,
+        &[
+            "third-party1",
+            "transitive-third-party1",
+            "third-party2",
+            "first-party",
+        ],
+    );
+    insta::assert_snapshot!("builtin-simple-explain", output);
+}
+
+#[test]
+fn builtin_simple_explain_disconnected() {
+    // (Fail) Explain a package with no audit chain, and the package that depends on it.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let store = explain_store(&metadata, None);
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    assert!(matches!(report.conclusion, Conclusion::FailForVet(_)));
+    let output = get_explain(&report, &["third-party2", "first-party"]);
+    insta::assert_snapshot!("builtin-simple-explain-disconnected", output);
+}
+
+#[test]
+fn builtin_simple_explain_json() {
+    // (Pass) The json form of the explanation.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let store = explain_store(&metadata, Some(unaudited(ver(DEFAULT_VER), SAFE_TO_DEPLOY)));
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let pkgidx = report
+        .graph
+        .nodes
+        .iter()
+        .position(|package| package.name == "third-party1")
+        .unwrap();
+    let mut output = Vec::new();
+    report.print_explain_json(&mut output, pkgidx).unwrap();
+    insta::assert_snapshot!(
+        "builtin-simple-explain-json",
+        String::from_utf8(output).unwrap()
+    );
+}
+
+fn get_why(report: &ResolveReport, store: &Store, name: &str) -> String {
+    let targets = report
+        .graph
+        .nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, package)| package.name == name)
+        .map(|(pkgidx, _)| pkgidx)
+        .collect::<Vec<_>>();
+    let paths = report.compute_why(store, &targets);
+    let mut output = Vec::new();
+    report.print_why_human(&mut output, name, &paths).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn builtin_simple_why() {
+    // Policy and audit dependency-criteria change what's required along a path.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let (mut config, mut audits, imports) = builtin_files_full_audited(&metadata);
+    config.policy.insert(
+        "first-party".to_owned(),
+        dep_policy([("third-party2", [SAFE_TO_RUN])]),
+    );
+    audits.audits.insert(
+        "third-party1".to_owned(),
+        vec![full_audit_dep(
+            ver(DEFAULT_VER),
+            SAFE_TO_DEPLOY,
+            [("transitive-third-party1", [SAFE_TO_RUN])],
+        )],
+    );
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let output = ["first-party", "transitive-third-party1", "third-party2"]
+        .iter()
+        .map(|name| get_why(&report, &store, name))
+        .collect::<Vec<_>>()
+        .join("\n");
+    insta::assert_snapshot!("builtin-simple-why", output);
+}
+
+#[test]
+fn builtin_dev_detection_why() {
+    // Dev-builds of workspace members are roots of their own.
+
+    let mock = MockMetadata::dev_detection();
+    let metadata = mock.metadata();
+    let (config, audits, imports) = builtin_files_full_audited(&metadata);
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let output = ["both", "simple-dev-indirect", "root", "nonexistent"]
+        .iter()
+        .map(|name| get_why(&report, &store, name))
+        .collect::<Vec<_>>()
+        .join("\n");
+    insta::assert_snapshot!("builtin-dev-detection-why", output);
+}
+
+#[test]
+fn builtin_simple_foreign_why() {
+    // The dependency-criteria of imported audits are mapped to ours.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let store = foreign_dep_criteria_store(&metadata, vec![], true);
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let output = get_why(&report, &store, "transitive-third-party1");
+    insta::assert_snapshot!("builtin-simple-foreign-why", output);
+}
+
+#[test]
+fn builtin_why_truncated() {
+    // There can be exponentially many paths to a package, so we only find the
+    // first few.
+
+    const LEFT: [&str; 8] = [
+        "left0", "left1", "left2", "left3", "left4", "left5", "left6", "left7",
+    ];
+    const RIGHT: [&str; 8] = [
+        "right0", "right1", "right2", "right3", "right4", "right5", "right6", "right7",
+    ];
+    // Every layer depends on both packages in the next one, so there are 2^8
+    // paths to the bottom.
+    let layer_deps = |layer: usize| match layer {
+        8 => vec![dep("bottom")],
+        _ => vec![dep(LEFT[layer]), dep(RIGHT[layer])],
+    };
+    let mut packages = vec![
+        MockPackage {
+            name: "root",
+            is_root: true,
+            is_first_party: true,
+            deps: layer_deps(0),
+            ..Default::default()
+        },
+        MockPackage {
+            name: "bottom",
+            ..Default::default()
+        },
+    ];
+    for layer in 0..8 {
+        for name in [LEFT[layer], RIGHT[layer]] {
+            packages.push(MockPackage {
+                name,
+                deps: layer_deps(layer + 1),
+                ..Default::default()
+            });
+        }
+    }
+    let mock = MockMetadata::new(packages);
+    let metadata = mock.metadata();
+    let (config, audits, imports) = builtin_files_full_audited(&metadata);
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let bottom = report
+        .graph
+        .nodes
+        .iter()
+        .position(|package| package.name == "bottom")
+        .unwrap();
+    let paths = report.compute_why(&store, &[bottom]);
+    assert_eq!(paths.paths.len(), MAX_WHY_PATHS);
+    assert!(paths.truncated);
+
+    let output = get_why(&report, &store, "bottom");
+    assert!(
+        output.ends_with("\n\n(only showing the first 100 paths to bottom)\n"),
+        "{output}"
+    );
+}
+
+fn alternative_blame_store(metadata: &Metadata) -> Store {
+    let (config, mut audits, imports) = builtin_files_no_unaudited(metadata);
+
+    // Every dependency is only safe-to-run, and `parent` has three audits that
+    // each need safe-to-deploy from a different set of them.
+    for (name, version) in [("expensive", DEFAULT_VER), ("cheap1", 2), ("cheap2", 3)] {
+        audits
+            .audits
+            .insert(name.to_owned(), vec![full_audit(ver(version), SAFE_TO_RUN)]);
+    }
+    audits.audits.insert(
+        "parent".to_owned(),
+        vec![
+            full_audit_dep(
+                ver(DEFAULT_VER),
+                SAFE_TO_DEPLOY,
+                [("cheap1", [SAFE_TO_RUN]), ("cheap2", [SAFE_TO_RUN])],
+            ),
+            full_audit_dep(
+                ver(DEFAULT_VER),
+                SAFE_TO_DEPLOY,
+                [("expensive", [SAFE_TO_RUN])],
+            ),
+            // Needs everything, so it's never the minimal thing to blame
+            full_audit(ver(DEFAULT_VER), SAFE_TO_DEPLOY),
+        ],
+    );
+
+    Store::mock(config, audits, imports)
+}
+
+#[test]
+fn builtin_alternative_blame() {
+    // (Fail) Only one set of deps is blamed, and the others are kept as alternatives.
+
+    let mock = MockMetadata::alternative_blame();
+    let metadata = mock.metadata();
+    let store = alternative_blame_store(&metadata);
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let failures = match &report.conclusion {
+        Conclusion::FailForVet(fail) => &fail.failures,
+        _ => panic!("expected a vetting failure"),
+    };
+    let blamed = failures
+        .keys()
+        .map(|&pkgidx| report.graph.nodes[pkgidx].name)
+        .collect::<Vec<_>>();
+    assert_eq!(blamed, ["expensive"]);
+
+    let output = get_explain(&report, &["parent"]);
+    insta::assert_snapshot!("builtin-alternative-blame", output);
+}
+
+#[test]
+fn builtin_alternative_blame_suggest() {
+    // (Fail) Suggest blames the alternative with the fewest lines to audit.
+
+    let mock = MockMetadata::alternative_blame();
+    let metadata = mock.metadata();
+    let store = alternative_blame_store(&metadata);
+    let cfg = mock_cfg(&metadata);
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let suggest = report.compute_suggest(&cfg, true).unwrap().unwrap();
+    let mut output = Vec::new();
+    suggest.print_human(&mut output, &report).unwrap();
+    insta::assert_snapshot!(
+        "builtin-alternative-blame-suggest",
+        String::from_utf8(output).unwrap()
+    );
+}
+
+#[test]
+fn builtin_suggest_unsuggested() {
+    // (Fail) Failures we couldn't suggest any audits for are listed, rather than
+    // just missing from the suggestions.
+
+    let mock = MockMetadata::alternative_blame();
+    let metadata = mock.metadata();
+    let store = alternative_blame_store(&metadata);
+    let cfg = mock_cfg(&metadata);
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let mut suggest = report.compute_suggest(&cfg, true).unwrap().unwrap();
+    assert!(suggest.unsuggested.is_empty());
+
+    suggest.unsuggested = suggest.failures.keys().copied().collect();
+    suggest.suggestions_by_criteria.clear();
+    let mut output = Vec::new();
+    suggest.print_human(&mut output, &report).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(
+        output.starts_with(
+            "could not suggest audits for (see the errors above):\n    cheap1:2.0.0\n    cheap2:3.0.0\n\n"
+        ),
+        "{output}"
+    );
+}
+
+#[test]
+fn builtin_alternative_blame_unrelated_criteria() {
+    // (Fail) A dependency that's missing unrelated criteria needs an audit for each
+    // of them, but only one for criteria that imply each other.
+    //
+    // Fixing `cheap1` costs 4 lines per audit, for safe-to-deploy (which implies
+    // safe-to-run), crypto-reviewed and fuzzed, so 12 in all. That's more than
+    // the 9 of `cheap2`, which only needs safe-to-deploy.
+
+    let mock = MockMetadata::alternative_blame();
+    let metadata = mock.metadata();
+    let (config, mut audits, imports) = builtin_files_no_unaudited(&metadata);
+    for name in ["crypto-reviewed", "fuzzed"] {
+        audits.criteria.insert(
+            name.to_owned(),
+            CriteriaEntry {
+                implies: vec![],
+                description: Some(name.to_owned()),
+                description_url: None,
+            },
+        );
+    }
+    audits.audits.insert(
+        "parent".to_owned(),
+        vec![
+            full_audit_dep(
+                ver(DEFAULT_VER),
+                SAFE_TO_DEPLOY,
+                [
+                    ("cheap1", vec![SAFE_TO_DEPLOY, "crypto-reviewed", "fuzzed"]),
+                    ("cheap2", vec![]),
+                    ("expensive", vec![]),
+                ],
+            ),
+            full_audit_dep(
+                ver(DEFAULT_VER),
+                SAFE_TO_DEPLOY,
+                [
+                    ("cheap1", vec![]),
+                    ("cheap2", vec![SAFE_TO_DEPLOY]),
+                    ("expensive", vec![]),
+                ],
+            ),
+        ],
+    );
+    let store = Store::mock(config, audits, imports);
+    let cfg = mock_cfg(&metadata);
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let suggest = report.compute_suggest(&cfg, true).unwrap().unwrap();
+    let blamed = suggest
+        .failures
+        .keys()
+        .map(|&pkgidx| report.graph.nodes[pkgidx].name)
+        .collect::<Vec<_>>();
+    assert_eq!(blamed, ["cheap2"]);
+    assert_eq!(suggest.total_lines, 9);
+}
+
+#[test]
+fn builtin_many_alternatives_capped() {
+    // (Fail) Each step of the chain 7 -> 8 -> 9 -> 10 can be audited needing either
+    // of two deps, so there are 16 minimal sets of deps to fix, but only a few are
+    // kept.
+
+    let mock = MockMetadata::many_alternatives();
+    let metadata = mock.metadata();
+    let (config, mut audits, imports) = builtin_files_no_unaudited(&metadata);
+    for name in LADDER_DEPS.iter().flatten() {
+        audits.audits.insert(
+            name.to_string(),
+            vec![full_audit(ver(DEFAULT_VER), SAFE_TO_RUN)],
+        );
+    }
+    let mut parent_audits = vec![];
+    for (step, pair) in LADDER_DEPS.iter().enumerate() {
+        let to = ver(7 + step as u64);
+        for needed in pair {
+            // Everything but `needed` only has to be safe-to-run
+            let dependency_criteria = LADDER_DEPS
+                .iter()
+                .flatten()
+                .filter(|&name| name != needed)
+                .map(|&name| (name, [SAFE_TO_RUN]));
+            parent_audits.push(if step == 0 {
+                full_audit_dep(to.clone(), SAFE_TO_DEPLOY, dependency_criteria)
+            } else {
+                delta_audit_dep(
+                    ver(6 + step as u64),
+                    to.clone(),
+                    SAFE_TO_DEPLOY,
+                    dependency_criteria,
+                )
+            });
+        }
+    }
+    audits.audits.insert("parent".to_owned(), parent_audits);
+    let store = Store::mock(config, audits, imports);
+
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let parent = report
+        .graph
+        .nodes
+        .iter()
+        .position(|package| package.name == "parent")
+        .unwrap();
+    let safe_to_deploy = report.criteria_mapper.index[SAFE_TO_DEPLOY];
+    match &report.results[parent].search_results[safe_to_deploy] {
+        SearchResult::PossiblyConnected { alternatives } => {
+            assert_eq!(alternatives.len(), MAX_FAILED_DEPS_ALTERNATIVES);
+            for failed_deps in alternatives {
+                assert_eq!(failed_deps.len(), LADDER_DEPS.len());
+            }
+        }
+        _ => panic!("expected parent to be blocked by its deps"),
+    }
+}
+
+#[test]
+fn builtin_diverging_chains_suggest() {
+    // (Fail) When the chains for each criteria don't share a version, suggest one
+    // audit per criteria if that's cheaper than a single full audit.
+    //
+    // The mocked diffstat is the difference of the squares of the major versions,
+    // so for `split` the deltas 9->10 and 8->10 (19 + 36 lines) beat a full audit
+    // (100 lines), but for `shared` the deltas 9->10 and 2->10 (19 + 96) don't.
+
+    let mock = MockMetadata::diverging_chains();
+    let metadata = mock.metadata();
+    let (mut config, mut audits, imports) = builtin_files_no_unaudited(&metadata);
+    audits.criteria.insert(
+        "crypto-reviewed".to_owned(),
+        CriteriaEntry {
+            implies: vec![],
+            description: Some("crypto reviewed".to_owned()),
+            description_url: None,
+        },
+    );
+    config.policy.insert(
+        "root".to_owned(),
+        self_policy([SAFE_TO_RUN, "crypto-reviewed"]),
+    );
+    audits.audits.insert(
+        "split".to_owned(),
+        vec![
+            full_audit(ver(9), SAFE_TO_RUN),
+            full_audit(ver(8), "crypto-reviewed"),
+        ],
+    );
+    audits.audits.insert(
+        "shared".to_owned(),
+        vec![
+            full_audit(ver(9), SAFE_TO_RUN),
+            full_audit(ver(2), "crypto-reviewed"),
+        ],
+    );
+    let cfg = mock_cfg(&metadata);
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, false).unwrap();
+    let suggest = report.compute_suggest(&cfg, true).unwrap().unwrap();
+    assert_eq!(suggest.total_lines, 19 + 36 + 100);
+    let mut output = Vec::new();
+    suggest.print_human(&mut output, &report).unwrap();
+    insta::assert_snapshot!(
+        "builtin-diverging-chains-suggest",
+        String::from_utf8(output).unwrap()
+    );
+}
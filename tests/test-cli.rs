@@ -296,3 +296,34 @@ fn test_project_dump_graph_full() {
     insta::assert_snapshot!("test-project-dump-graph-full", format_outputs(&output));
     assert!(output.status.success(), "{}", output.status);
 }
+
+#[test]
+fn test_project_import_without_terminal() {
+    let project = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("test-project");
+    let registry = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("registry.toml");
+    let bin = env!("CARGO_BIN_EXE_cargo-vet");
+    let output = Command::new(bin)
+        .current_dir(&project)
+        .arg("vet")
+        .arg("--manifest-path")
+        .arg("Cargo.toml")
+        .arg("import")
+        .arg("firefox")
+        .arg("--registry")
+        .arg(&registry)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success(), "{stdout}");
+    assert!(
+        stderr.contains("pass --map-builtins or --no-map-builtins"),
+        "{stderr}"
+    );
+}